@@ -0,0 +1,85 @@
+//! Compare sending frames one LED at a time against sending them from a
+//! buffer, using a fake SPI bus that counts how it's used.
+
+use dotstar::embedded_hal::blocking::spi;
+use dotstar::{frame_len, ColorRgb, DotstarStrip, LightStrip};
+
+use std::time::Instant;
+
+const NUM_LIGHTS: usize = 300;
+const NUM_FRAMES: u32 = 1000;
+
+/// A fake SPI bus. Real buses have a fixed cost per `write` call (setting up
+/// DMA, waiting for the peripheral, etc.); `overhead` simulates it by
+/// spinning.
+struct CountingSpi {
+    writes: usize,
+    bytes: usize,
+    overhead: u32,
+}
+
+impl CountingSpi {
+    fn new(overhead: u32) -> CountingSpi {
+        CountingSpi {
+            writes: 0,
+            bytes: 0,
+            overhead,
+        }
+    }
+}
+
+impl spi::Write<u8> for CountingSpi {
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), ()> {
+        self.writes += 1;
+        self.bytes += words.len();
+        for i in 0..self.overhead {
+            std::hint::black_box(i);
+        }
+        Ok(())
+    }
+}
+
+fn run<S: LightStrip>(name: &str, strip: &mut S) {
+    let mut lights = [ColorRgb::black(); NUM_LIGHTS];
+    let start = Instant::now();
+    for frame in 0..NUM_FRAMES {
+        for (i, light) in lights.iter_mut().enumerate() {
+            let value = (i as u32 + frame) as u8;
+            *light = ColorRgb {
+                r: value,
+                g: value,
+                b: value,
+            };
+        }
+        if strip.show(&lights).is_err() {
+            panic!("Failed to send frame");
+        }
+    }
+    let per_frame = start.elapsed() / NUM_FRAMES;
+    println!("{:>12}: {:>10?} per frame", name, per_frame);
+}
+
+fn main() {
+    println!("{} lights, {} frames", NUM_LIGHTS, NUM_FRAMES);
+
+    let mut unbuffered = DotstarStrip::new(CountingSpi::new(200));
+    run("unbuffered", &mut unbuffered);
+
+    let mut buffered =
+        DotstarStrip::<_, { frame_len(NUM_LIGHTS) }>::new_buffered(
+            CountingSpi::new(200),
+        );
+    run("buffered", &mut buffered);
+
+    let (unbuffered, buffered) = (unbuffered.release(), buffered.release());
+    println!(
+        "{:>12}: {} writes, {} bytes",
+        "unbuffered", unbuffered.writes, unbuffered.bytes
+    );
+    println!(
+        "{:>12}: {} writes, {} bytes",
+        "buffered", buffered.writes, buffered.bytes
+    );
+}
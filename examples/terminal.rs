@@ -6,7 +6,6 @@ use std::io;
 use std::io::Write;
 use std::thread;
 
-use termion;
 use termion::event::Key;
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
@@ -98,7 +97,7 @@ impl LightStrip for TerminalRenderer {
     type Error = io::Error;
 
     fn show(&mut self, lights: &[ColorRgb]) -> io::Result<()> {
-        write_lights(&mut self.stdout, &lights)?;
+        write_lights(&mut self.stdout, lights)?;
         write_instructions(&mut self.stdout)?;
         self.stdout.flush()
     }
@@ -164,7 +163,7 @@ where
     Ok(())
 }
 
-static LINES: [(u8, u8, u8, &'static str); 14] = [
+static LINES: [(u8, u8, u8, &str); 14] = [
     (255, 255, 255, "1-8: switch mode"),
     (255, 255, 255, "q,Esc: quit"),
     (0, 0, 0, ""),
@@ -0,0 +1,196 @@
+use crate::color::{
+    linear_rgb_to_srgb, srgb_to_linear_rgb, ColorLinearRgb, ColorRgb,
+};
+use crate::color_constants::{LINEAR_RGB_TO_SRGB, RGB_LINEAR_TO_XYZ};
+use crate::int_math::mix;
+
+/// sRGB colors with an alpha channel, for layering over other colors. `a`
+/// ranges from 0 (fully transparent) to 255 (fully opaque).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorRgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// How a layer's colors combine with the colors under them. All of them work
+/// in linear light, like mixing lights, before the layer's alpha fades
+/// between the result and the color underneath.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlendMode {
+    /// Cover the color underneath.
+    Normal,
+    /// Add the light of both colors, like two lights shining on one spot.
+    Add,
+    /// Multiply the colors, which darkens: white leaves the color underneath
+    /// unchanged, and black makes black.
+    Multiply,
+    /// The opposite of `Multiply`, which lightens: black leaves the color
+    /// underneath unchanged, and white makes white.
+    Screen,
+    /// The brighter of the two colors in each channel.
+    Max,
+    /// Whichever of the two colors is brighter overall.
+    Lighten,
+}
+
+impl ColorRgba {
+    pub fn transparent() -> ColorRgba {
+        ColorRgba {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        }
+    }
+
+    /// The color without its alpha channel.
+    pub fn rgb(self) -> ColorRgb {
+        ColorRgb {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+        }
+    }
+}
+
+impl From<ColorRgb> for ColorRgba {
+    /// An opaque version of the color.
+    fn from(color: ColorRgb) -> ColorRgba {
+        ColorRgba {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: 255,
+        }
+    }
+}
+
+impl ColorRgb {
+    /// Blend `layer` over this color.
+    pub fn blend(self, layer: ColorRgba, mode: BlendMode) -> ColorRgb {
+        if layer.a == 0 {
+            return self;
+        }
+        let under = srgb_to_linear_rgb(self);
+        let over = srgb_to_linear_rgb(layer.rgb());
+        let white = LINEAR_RGB_TO_SRGB[255];
+        let channel = |u: i32, o: i32| {
+            let blended = match mode {
+                BlendMode::Normal => o,
+                BlendMode::Add => (u + o).min(white),
+                BlendMode::Multiply => {
+                    (u as i64 * o as i64 / white as i64) as i32
+                }
+                BlendMode::Screen => {
+                    u + o - (u as i64 * o as i64 / white as i64) as i32
+                }
+                BlendMode::Max => u.max(o),
+                BlendMode::Lighten => {
+                    if luminance(over) > luminance(under) {
+                        o
+                    } else {
+                        u
+                    }
+                }
+            };
+            mix(u, blended, layer.a)
+        };
+        let blended = ColorLinearRgb {
+            r: channel(under.r, over.r),
+            g: channel(under.g, over.g),
+            b: channel(under.b, over.b),
+        };
+        match linear_rgb_to_srgb(blended) {
+            Ok(color) => color,
+            Err(color) => color,
+        }
+    }
+}
+
+/// Blend a layer of colors (e.g. sparkles) over `lights`, e.g. the output of
+/// another light show. Lights past the end of `layer` are left unchanged.
+pub fn composite(
+    lights: &mut [ColorRgb],
+    layer: &[ColorRgba],
+    mode: BlendMode,
+) {
+    for (light, over) in lights.iter_mut().zip(layer) {
+        *light = light.blend(*over, mode);
+    }
+}
+
+// The relative luminance of a linear RGB color, i.e. the Y of CIE-XYZ.
+fn luminance(rgb: ColorLinearRgb) -> i64 {
+    let [r, g, b] = RGB_LINEAR_TO_XYZ[1];
+    r as i64 * rgb.r as i64 + g as i64 * rgb.g as i64 + b as i64 * rgb.b as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(v: u8) -> ColorRgb {
+        ColorRgb { r: v, g: v, b: v }
+    }
+
+    fn rgba(r: u8, g: u8, b: u8, a: u8) -> ColorRgba {
+        ColorRgba { r, g, b, a }
+    }
+
+    #[test]
+    fn test_alpha() {
+        let red = ColorRgb { r: 255, g: 0, b: 0 };
+        let blue = rgba(0, 0, 255, 255);
+        assert_eq!(red.blend(blue, BlendMode::Normal), blue.rgb());
+        assert_eq!(red.blend(ColorRgba::transparent(), BlendMode::Add), red);
+        // Half of each, in linear light.
+        let half = ColorRgba { a: 128, ..blue };
+        assert_eq!(
+            red.blend(half, BlendMode::Normal),
+            ColorRgb {
+                r: 187,
+                g: 0,
+                b: 187
+            }
+        );
+        assert_eq!(ColorRgba::from(red), rgba(255, 0, 0, 255));
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let base = ColorRgb {
+            r: 200,
+            g: 100,
+            b: 0,
+        };
+        let layer = rgba(100, 100, 100, 255);
+        let blend = |mode| {
+            let ColorRgb { r, g, b } = base.blend(layer, mode);
+            (r, g, b)
+        };
+        // Checked against floating point, all within 1/256.
+        assert_eq!(blend(BlendMode::Add), (219, 138, 100));
+        assert_eq!(blend(BlendMode::Multiply), (77, 34, 0));
+        assert_eq!(blend(BlendMode::Screen), (208, 134, 100));
+        assert_eq!(blend(BlendMode::Max), (200, 100, 100));
+        assert_eq!(blend(BlendMode::Lighten), (200, 100, 0));
+
+        // White and black leave colors unchanged.
+        let white = rgba(255, 255, 255, 255);
+        let black = rgba(0, 0, 0, 255);
+        assert_eq!(base.blend(white, BlendMode::Multiply), base);
+        assert_eq!(base.blend(black, BlendMode::Screen), base);
+        assert_eq!(base.blend(black, BlendMode::Add), base);
+        assert_eq!(gray(250).blend(white, BlendMode::Add), gray(255));
+    }
+
+    #[test]
+    fn test_composite() {
+        let mut lights = [gray(10); 3];
+        let sparkles = [rgba(255, 255, 255, 255), ColorRgba::transparent()];
+        composite(&mut lights, &sparkles, BlendMode::Add);
+        assert_eq!(lights, [gray(255), gray(10), gray(10)]);
+    }
+}
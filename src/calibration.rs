@@ -0,0 +1,80 @@
+use crate::color::ColorRgb16;
+
+/// The identity matrix, scaled by 12 bits.
+const IDENTITY: [[i32; 3]; 3] = [[4096, 0, 0], [0, 4096, 0], [0, 0, 4096]];
+
+/// Corrects for differences between batches of LEDs, so that the same color
+/// looks the same on each. For example, a batch with a strong red can have its
+/// red gain lowered.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Calibration {
+    /// How much to scale the red, green, and blue channels by, scaled by 12
+    /// bits: 4096 leaves a channel unchanged.
+    pub gain: [i32; 3],
+    /// A matrix for mixing the channels together, scaled by 12 bits like
+    /// `XYZ_TO_RGB_LINEAR`. Applied before the gains.
+    pub matrix: Option<[[i32; 3]; 3]>,
+}
+
+impl Calibration {
+    /// Scale each channel, without mixing them.
+    pub fn from_gain(r: i32, g: i32, b: i32) -> Calibration {
+        Calibration {
+            gain: [r, g, b],
+            matrix: None,
+        }
+    }
+
+    /// Apply this calibration to a color, clamping the result.
+    pub fn apply(self, color: ColorRgb16) -> ColorRgb16 {
+        let rgb = [color.r as i64, color.g as i64, color.b as i64];
+        let t = self.matrix.unwrap_or(IDENTITY);
+        let channel = |i: usize| {
+            let mixed = (t[i][0] as i64 * rgb[0]
+                + t[i][1] as i64 * rgb[1]
+                + t[i][2] as i64 * rgb[2])
+                >> 12;
+            let scaled = (mixed * self.gain[i] as i64) >> 12;
+            scaled.clamp(0, 65535) as u16
+        };
+        ColorRgb16 {
+            r: channel(0),
+            g: channel(1),
+            b: channel(2),
+        }
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Calibration {
+        Calibration::from_gain(4096, 4096, 4096)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(calibration: Calibration, r: u16, g: u16, b: u16) -> [u16; 3] {
+        let color = calibration.apply(ColorRgb16 { r, g, b });
+        [color.r, color.g, color.b]
+    }
+
+    #[test]
+    fn test_calibration() {
+        let none = Calibration::default();
+        assert_eq!(apply(none, 0, 1000, 65535), [0, 1000, 65535]);
+
+        let gain = Calibration::from_gain(2048, 4096, 8192);
+        assert_eq!(apply(gain, 1000, 1000, 1000), [500, 1000, 2000]);
+        assert_eq!(apply(gain, 65535, 65535, 65535), [32767, 65535, 65535]);
+
+        // Swap red and blue, and bleed some red into green.
+        let matrix = Calibration {
+            gain: [4096, 4096, 4096],
+            matrix: Some([[0, 0, 4096], [-1024, 4096, 0], [4096, 0, 0]]),
+        };
+        assert_eq!(apply(matrix, 4000, 2000, 0), [0, 1000, 4000]);
+        assert_eq!(apply(matrix, 4000, 0, 0), [0, 0, 4000]);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::color_constants::*;
+use crate::gamma::Gamma;
+use crate::int_math::{atan2, cos, icbrt, isqrt_round, sin};
 
 /// [CIE-LAB](https://en.wikipedia.org/wiki/CIELAB_color_space#CIELAB) colors.
 /// `l` ranges from 0 to 99. The range of `a` and `b` is complex and reflects
@@ -10,6 +12,28 @@ pub struct ColorLab {
     pub b: i8,
 }
 
+/// CIE-LAB colors with 8 bits of fraction, for when whole steps of `ColorLab`
+/// aren't smooth enough, e.g. for slow fades. `l`, `a`, and `b` are scaled by
+/// 256, so `l` ranges from 0 to 25600.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorLab16 {
+    pub l: i16,
+    pub a: i16,
+    pub b: i16,
+}
+
+/// [CIE-LCh](https://en.wikipedia.org/wiki/CIELAB_color_space#Cylindrical_model)
+/// colors: CIE-LAB in polar coordinates. `l` is the same as in `ColorLab`, `c`
+/// (the chroma) is the distance from gray, and `h` is the hue in degrees,
+/// counterclockwise from the `a` axis (pinkish red) towards the `b` axis
+/// (yellow).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorLch {
+    pub l: i8,
+    pub c: u8,
+    pub h: i16,
+}
+
 /// [sRGB](https://en.wikipedia.org/wiki/SRGB) colors.
 /// `r`, `g`, and `b` range from 0 to 255.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -19,6 +43,15 @@ pub struct ColorRgb {
     pub b: u8,
 }
 
+/// sRGB colors with 16 bits per channel, for when 8 bits isn't enough
+/// precision. `r`, `g`, and `b` range from 0 to 65535.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorRgb16 {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+}
+
 impl ColorLab {
     /// Convert to sRGB. If this color is invalid, return an `Err` of a clamped
     /// version of it.
@@ -35,6 +68,33 @@ impl ColorLab {
         }
     }
 
+    /// Convert to sRGB. If this color is invalid, reduce its chroma (keeping
+    /// its hue and lightness) until it's valid. This changes the color less
+    /// than clamping, which can shift its hue.
+    pub fn to_srgb_mapped(self) -> ColorRgb {
+        let l = self.l.clamp(0, 100);
+        // Scale a and b by `scale` out of 256.
+        let scaled = |scale: i32| ColorLab {
+            l,
+            a: (self.a as i32 * scale / 256) as i8,
+            b: (self.b as i32 * scale / 256) as i8,
+        };
+        if let Ok(color) = scaled(256).to_srgb() {
+            return color;
+        }
+        // Binary search for the largest scale that's valid.
+        let (mut lo, mut hi) = (0, 256);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if scaled(mid).is_valid() {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        scaled(lo).to_srgb_clamped()
+    }
+
     /// Check if this is a valid (i.e., representable) color.
     pub fn is_valid(self) -> bool {
         lab_to_srgb(self).is_ok()
@@ -44,9 +104,154 @@ impl ColorLab {
     pub fn max_radius(self) -> i8 {
         max_lab_radius(self)
     }
+
+    /// The color of white light with a correlated color temperature of
+    /// `kelvin` (see `ColorRgb::from_kelvin`), at lightness `l`.
+    pub fn from_kelvin(kelvin: u16, l: i8) -> ColorLab {
+        let gray = ColorLab { l, a: 0, b: 0 };
+        xyz_to_lab(kelvin_to_xyz(kelvin, lab_to_xyz(gray.into()).y))
+    }
+
+    /// Convert to polar coordinates.
+    pub fn to_lch(self) -> ColorLch {
+        let (a, b) = (self.a as i32, self.b as i32);
+        ColorLch {
+            l: self.l,
+            c: isqrt_round((a * a + b * b) as u64) as u8,
+            h: atan2(b, a) as i16,
+        }
+    }
+
+    /// Move this color `chroma` away in the direction of `hue` (in degrees,
+    /// as in `ColorLch`), keeping its lightness.
+    pub fn shift(self, chroma: u8, hue: i16) -> ColorLab {
+        let offset = ColorLch {
+            l: self.l,
+            c: chroma,
+            h: hue,
+        }
+        .to_lab();
+        ColorLab {
+            l: self.l,
+            a: self.a.saturating_add(offset.a),
+            b: self.b.saturating_add(offset.b),
+        }
+    }
+}
+
+impl ColorLab16 {
+    /// Convert to 16-bit sRGB. If this color is invalid, return an `Err` of a
+    /// clamped version of it.
+    pub fn to_srgb16(self) -> Result<ColorRgb16, ColorRgb16> {
+        linear_rgb_to_srgb16(xyz_to_linear_rgb(lab_to_xyz(self)))
+    }
+
+    /// Convert to 16-bit sRGB. If this color is invalid, automatically clamp
+    /// it to a valid color.
+    pub fn to_srgb16_clamped(self) -> ColorRgb16 {
+        match self.to_srgb16() {
+            Ok(color) => color,
+            Err(color) => color,
+        }
+    }
+
+    /// Check if this is a valid (i.e., representable) color.
+    pub fn is_valid(self) -> bool {
+        self.to_srgb16().is_ok()
+    }
+}
+
+impl From<ColorLab> for ColorLab16 {
+    fn from(color: ColorLab) -> ColorLab16 {
+        ColorLab16 {
+            l: (color.l as i16) << 8,
+            a: (color.a as i16) << 8,
+            b: (color.b as i16) << 8,
+        }
+    }
+}
+
+impl From<ColorLab16> for ColorLab {
+    fn from(color: ColorLab16) -> ColorLab {
+        let round = |c: i16| ((c as i32 + 128) >> 8).clamp(-128, 127) as i8;
+        ColorLab {
+            l: round(color.l),
+            a: round(color.a),
+            b: round(color.b),
+        }
+    }
+}
+
+impl ColorLch {
+    /// Convert to rectangular coordinates.
+    pub fn to_lab(self) -> ColorLab {
+        let (c, h) = (self.c as i32, self.h as i32);
+        ColorLab {
+            l: self.l,
+            a: cos(h, c).clamp(-128, 127) as i8,
+            b: sin(h, c).clamp(-128, 127) as i8,
+        }
+    }
+
+    /// Convert to sRGB, clamping it to a valid color (see
+    /// `ColorLab::to_srgb_clamped`).
+    pub fn to_srgb_clamped(self) -> ColorRgb {
+        self.to_lab().to_srgb_clamped()
+    }
+
+    /// Rotate the hue by `degrees`, counterclockwise. The result's hue is
+    /// between 0 and 359.
+    pub fn rotate_hue(self, degrees: i16) -> ColorLch {
+        ColorLch {
+            h: (self.h as i32 + degrees as i32).rem_euclid(360) as i16,
+            ..self
+        }
+    }
+
+    /// Reduce the chroma so that this color is valid at every hue, keeping
+    /// its lightness (see `ColorLab::max_radius`).
+    pub fn clamp_chroma(self) -> ColorLch {
+        let gray = ColorLab {
+            l: self.l,
+            a: 0,
+            b: 0,
+        };
+        ColorLch {
+            c: self.c.min(gray.max_radius() as u8),
+            ..self
+        }
+    }
 }
 
 impl ColorRgb {
+    /// The brightest color of white light with a correlated color temperature
+    /// of `kelvin`, from 1000K (candlelight) through about 2700K (warm white
+    /// bulbs) and 6500K (daylight) to 12000K (blue sky). Temperatures outside
+    /// that range are clamped to it.
+    pub fn from_kelvin(kelvin: u16) -> ColorRgb {
+        let rgb = xyz_to_linear_rgb(kelvin_to_xyz(kelvin, 100 << 12));
+        // Scale the largest channel up to full brightness. Very warm whites
+        // have slightly negative blue, which is clamped.
+        let max = rgb.r.max(rgb.g).max(rgb.b) as i64;
+        let full = LINEAR_RGB_TO_SRGB[255] as i64;
+        let scale = |c: i32| (c.max(0) as i64 * full / max) as i32;
+        let rgb = ColorLinearRgb {
+            r: scale(rgb.r),
+            g: scale(rgb.g),
+            b: scale(rgb.b),
+        };
+        match linear_rgb_to_srgb(rgb) {
+            Ok(color) => color,
+            Err(color) => color,
+        }
+    }
+
+    /// Convert to CIE-LAB, e.g. to show a color picked in sRGB with the LAB
+    /// based light shows.
+    pub fn to_lab(self) -> ColorLab {
+        srgb_to_lab(self)
+    }
+
     pub fn black() -> ColorRgb {
         ColorRgb { r: 0, g: 0, b: 0 }
     }
@@ -63,6 +268,40 @@ impl ColorRgb {
     }
 }
 
+impl ColorRgb16 {
+    pub fn black() -> ColorRgb16 {
+        ColorRgb16 { r: 0, g: 0, b: 0 }
+    }
+
+    /// Apply gamma correction, like `ColorRgb::correct_gamma`, but keeping 16
+    /// bits of precision in the result.
+    pub fn correct_gamma(self) -> ColorRgb16 {
+        Gamma::default().correct(self)
+    }
+}
+
+impl From<ColorRgb> for ColorRgb16 {
+    fn from(color: ColorRgb) -> ColorRgb16 {
+        // Multiplying by 257 maps 255 to 65535.
+        ColorRgb16 {
+            r: color.r as u16 * 257,
+            g: color.g as u16 * 257,
+            b: color.b as u16 * 257,
+        }
+    }
+}
+
+impl From<ColorRgb16> for ColorRgb {
+    fn from(color: ColorRgb16) -> ColorRgb {
+        let round = |c: u16| ((c as u32 * 255 + 32767) / 65535) as u8;
+        ColorRgb {
+            r: round(color.r),
+            g: round(color.g),
+            b: round(color.b),
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 struct ColorXyz {
     x: i32,
@@ -71,35 +310,38 @@ struct ColorXyz {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-struct ColorLinearRgb {
-    r: i32,
-    g: i32,
-    b: i32,
+pub(crate) struct ColorLinearRgb {
+    pub(crate) r: i32,
+    pub(crate) g: i32,
+    pub(crate) b: i32,
 }
 
 // The inverse of function f, defined here:
 //    https://en.wikipedia.org/wiki/CIELAB_color_space#CIELAB
-// Both input and output scaled by 12 bits.
-fn f_inv(t: i32) -> i32 {
-    if t > DELTA {
-        (((t * t) >> 12) * t) >> 12
+// Both input and output scaled by 20 bits.
+fn f_inv(t: i64) -> i64 {
+    let delta = (6 << 20) / 29;
+    if t > delta {
+        (((t * t) >> 20) * t) >> 20
     } else {
-        ((3 * (DELTA >> 6) * (DELTA >> 6)) * (t - (4 << 12) / 29)) >> 12
+        (((3 * delta * delta) >> 20) * (t - (4 << 20) / 29)) >> 20
     }
 }
 
 // Convert from CIE-lab to CIE-XYZ, defined here:
 //    https://en.wikipedia.org/wiki/CIELAB_color_space#CIELAB
-// Output scaled by 12 bits.
-fn lab_to_xyz(lab: ColorLab) -> ColorXyz {
-    let ColorLab { l, a, b } = lab;
-    let l_adj: i32 = (((l as i32) + 16) << 12) / 116;
-    let a_adj: i32 = ((a as i32) << 12) / 500;
-    let b_adj: i32 = -((b as i32) << 12) / 200;
+// Input scaled by 8 bits, as in `ColorLab16`. Output scaled by 12 bits.
+fn lab_to_xyz(lab: ColorLab16) -> ColorXyz {
+    let ColorLab16 { l, a, b } = lab;
+    let l_adj = ((l as i64 + (16 << 8)) << 20) / (116 << 8);
+    let a_adj = ((a as i64) << 20) / (500 << 8);
+    let b_adj = -((b as i64) << 20) / (200 << 8);
+    // Scale from 20 bits to 12, rounding.
+    let scale = |d65: i32, t: i64| ((d65 as i64 * f_inv(t) + 128) >> 8) as i32;
     ColorXyz {
-        x: D65_ILLUMINANT[0] * f_inv(l_adj + a_adj),
-        y: D65_ILLUMINANT[1] * f_inv(l_adj),
-        z: D65_ILLUMINANT[2] * f_inv(l_adj + b_adj),
+        x: scale(D65_ILLUMINANT[0], l_adj + a_adj),
+        y: scale(D65_ILLUMINANT[1], l_adj),
+        z: scale(D65_ILLUMINANT[2], l_adj + b_adj),
     }
 }
 
@@ -107,19 +349,16 @@ fn lab_to_xyz(lab: ColorLab) -> ColorXyz {
 //    https://en.wikipedia.org/wiki/SRGB
 // Input and output scaled by 12 bits.
 fn xyz_to_linear_rgb(xyz: ColorXyz) -> ColorLinearRgb {
-    let ColorXyz {
-        mut x,
-        mut y,
-        mut z,
-    } = xyz;
-    x >>= 6;
-    y >>= 6;
-    z >>= 6;
+    let (x, y, z) = (xyz.x as i64, xyz.y as i64, xyz.z as i64);
     let t = XYZ_TO_RGB_LINEAR;
+    let row = |i: usize| {
+        ((t[i][0] as i64 * x + t[i][1] as i64 * y + t[i][2] as i64 * z) >> 12)
+            as i32
+    };
     ColorLinearRgb {
-        r: (t[0][0] * x + t[0][1] * y + t[0][2] * z) >> 6,
-        g: (t[1][0] * x + t[1][1] * y + t[1][2] * z) >> 6,
-        b: (t[2][0] * x + t[2][1] * y + t[2][2] * z) >> 6,
+        r: row(0),
+        g: row(1),
+        b: row(2),
     }
 }
 
@@ -127,7 +366,9 @@ fn xyz_to_linear_rgb(xyz: ColorXyz) -> ColorLinearRgb {
 //    https://en.wikipedia.org/wiki/SRGB
 // Assumes that the range of LAB's L value is [0, 100]
 // and that the range of the output RGB values is [0, 256]
-fn linear_rgb_to_srgb(rgb: ColorLinearRgb) -> Result<ColorRgb, ColorRgb> {
+pub(crate) fn linear_rgb_to_srgb(
+    rgb: ColorLinearRgb,
+) -> Result<ColorRgb, ColorRgb> {
     let ColorLinearRgb { r, g, b } = rgb;
     match (gamma(r), gamma(g), gamma(b)) {
         (Ok(r), Ok(g), Ok(b)) => Ok(ColorRgb { r, g, b }),
@@ -154,13 +395,153 @@ fn gamma(u: i32) -> Result<u8, u8> {
     }
 }
 
+// Like `gamma`, but with 16 bits of output, interpolating between the table
+// entries. Input scaled by 12 bits.
+fn gamma16(u: i32) -> Result<u16, u16> {
+    match LINEAR_RGB_TO_SRGB.binary_search(&u) {
+        Ok(i) => Ok(i as u16 * 257),
+        Err(0) => Err(0),
+        Err(256) => Err(u16::MAX),
+        Err(i) => {
+            let lo = LINEAR_RGB_TO_SRGB[i - 1];
+            let hi = LINEAR_RGB_TO_SRGB[i];
+            Ok(((i as i32 - 1) * 257 + (u - lo) * 257 / (hi - lo)) as u16)
+        }
+    }
+}
+
+// Convert from linear RGB to 16-bit sRGB, like `linear_rgb_to_srgb`.
+fn linear_rgb_to_srgb16(rgb: ColorLinearRgb) -> Result<ColorRgb16, ColorRgb16> {
+    let (r, g, b) = (gamma16(rgb.r), gamma16(rgb.g), gamma16(rgb.b));
+    let valid = r.is_ok() && g.is_ok() && b.is_ok();
+    let value = |c: Result<u16, u16>| match c {
+        Ok(c) => c,
+        Err(c) => c,
+    };
+    let color = ColorRgb16 {
+        r: value(r),
+        g: value(g),
+        b: value(b),
+    };
+    if valid {
+        Ok(color)
+    } else {
+        Err(color)
+    }
+}
+
 fn lab_to_srgb(lab: ColorLab) -> Result<ColorRgb, ColorRgb> {
-    linear_rgb_to_srgb(xyz_to_linear_rgb(lab_to_xyz(lab)))
+    linear_rgb_to_srgb(xyz_to_linear_rgb(lab_to_xyz(lab.into())))
+}
+
+// The inverse of `gamma`, for converting from sRGB to linear RGB. Uses the
+// middle of the range of linear values that `gamma` maps to `u`, except that
+// black and white stay at the ends of the range.
+// Output scaled by 12 bits.
+fn gamma_inv(u: u8) -> i32 {
+    let i = u as usize;
+    if i == 0 || i == 255 {
+        LINEAR_RGB_TO_SRGB[i]
+    } else {
+        (LINEAR_RGB_TO_SRGB[i] + LINEAR_RGB_TO_SRGB[i + 1]) / 2
+    }
+}
+
+// Convert from sRGB to linear RGB, the inverse of `linear_rgb_to_srgb`.
+// Output scaled by 12 bits.
+pub(crate) fn srgb_to_linear_rgb(rgb: ColorRgb) -> ColorLinearRgb {
+    ColorLinearRgb {
+        r: gamma_inv(rgb.r),
+        g: gamma_inv(rgb.g),
+        b: gamma_inv(rgb.b),
+    }
+}
+
+// Convert from linear RGB to CIE-XYZ, the inverse of `xyz_to_linear_rgb`.
+// Input and output scaled by 12 bits.
+fn linear_rgb_to_xyz(rgb: ColorLinearRgb) -> ColorXyz {
+    let ColorLinearRgb {
+        mut r,
+        mut g,
+        mut b,
+    } = rgb;
+    r >>= 6;
+    g >>= 6;
+    b >>= 6;
+    let t = RGB_LINEAR_TO_XYZ;
+    ColorXyz {
+        x: (t[0][0] * r + t[0][1] * g + t[0][2] * b) >> 6,
+        y: (t[1][0] * r + t[1][1] * g + t[1][2] * b) >> 6,
+        z: (t[2][0] * r + t[2][1] * g + t[2][2] * b) >> 6,
+    }
+}
+
+// The function f, defined here:
+//    https://en.wikipedia.org/wiki/CIELAB_color_space#CIELAB
+// Both input and output scaled by 12 bits.
+fn f(t: i32) -> i32 {
+    let t = t.max(0) as i64;
+    if t > (DELTA as i64).pow(3) >> 24 {
+        icbrt((t << 24) as u64) as i32
+    } else {
+        ((t << 12) / ((3 * DELTA as i64 * DELTA as i64) >> 12)) as i32
+            + (4 << 12) / 29
+    }
+}
+
+// Convert from CIE-XYZ to CIE-lab, the inverse of `lab_to_xyz`.
+// Input scaled by 12 bits.
+fn xyz_to_lab(xyz: ColorXyz) -> ColorLab {
+    let fx = f(xyz.x / D65_ILLUMINANT[0]);
+    let fy = f(xyz.y / D65_ILLUMINANT[1]);
+    let fz = f(xyz.z / D65_ILLUMINANT[2]);
+    // Scale from 12 bits, rounding.
+    let unscale = |v: i32| (v + (1 << 11)) >> 12;
+    ColorLab {
+        l: (unscale(116 * fy) - 16).clamp(0, 100) as i8,
+        a: unscale(500 * (fx - fy)).clamp(-128, 127) as i8,
+        b: unscale(200 * (fy - fz)).clamp(-128, 127) as i8,
+    }
+}
+
+fn srgb_to_lab(rgb: ColorRgb) -> ColorLab {
+    xyz_to_lab(linear_rgb_to_xyz(srgb_to_linear_rgb(rgb)))
+}
+
+// The chromaticity of a black body at `kelvin`, in CIE 1960 (u, v)
+// coordinates scaled by 20 bits, using Krystek's approximation of the
+// Planckian locus:
+//    https://en.wikipedia.org/wiki/Planckian_locus#Approximation
+fn kelvin_to_uv(kelvin: u16) -> (i64, i64) {
+    let t = kelvin.clamp(1000, 12000) as i64;
+    // Each coefficient is scaled by 10^10, and by 1000 per power of `t`.
+    let poly = |c: [i64; 3]| c[0] + c[1] * t / 1000 + c[2] * t * t / 1_000_000;
+    let u = (poly([8601177570, 1541182540, 1286412120]) << 20)
+        / poly([10000000000, 8424202350, 7081451630]);
+    let v = (poly([3173987260, 422806245, 420481691]) << 20)
+        / poly([10000000000, -289741816, 1614560530]);
+    (u, v)
+}
+
+// Convert a color temperature to CIE-XYZ, with luminance `y`, described here:
+//    https://en.wikipedia.org/wiki/CIE_1960_color_space
+// Output scaled by 12 bits, like `y`.
+fn kelvin_to_xyz(kelvin: u16, y: i32) -> ColorXyz {
+    let (u, v) = kelvin_to_uv(kelvin);
+    let y = y as i64;
+    ColorXyz {
+        x: (y * 3 * u / (2 * v)) as i32,
+        y: y as i32,
+        z: (y * ((4 << 20) - u - 10 * v) / (2 * v)) as i32,
+    }
 }
 
 fn max_lab_radius(lab: ColorLab) -> i8 {
     fn lookup(li: i8, ai: i8, bi: i8) -> i8 {
-        if li < 1 || li > 12 || ai < -11 || ai > 10 || bi < -11 || bi > 10 {
+        if !(1..=12).contains(&li)
+            || !(-11..=10).contains(&ai)
+            || !(-11..=10).contains(&bi)
+        {
             0
         } else {
             MAX_LAB_RADIUS[(li - 1) as usize][(ai + 11) as usize]
@@ -201,6 +582,11 @@ mod tests {
         (r, g, b)
     }
 
+    fn to_lab(r: u8, g: u8, b: u8) -> (i8, i8, i8) {
+        let ColorLab { l, a, b } = ColorRgb { r, g, b }.to_lab();
+        (l, a, b)
+    }
+
     fn radius(l: i8, a: i8, b: i8) -> i8 {
         ColorLab { l, a, b }.max_radius()
     }
@@ -211,15 +597,15 @@ mod tests {
     #[test]
     fn test_grayscale() {
         assert_eq!(convert(0, 0, 0), (0, 0, 0));
-        assert_eq!(convert(1, 0, 0), (2, 3, 2));
-        assert_eq!(convert(2, 0, 0), (5, 6, 6));
+        assert_eq!(convert(1, 0, 0), (3, 3, 3));
+        assert_eq!(convert(2, 0, 0), (7, 7, 7));
         assert_eq!(convert(3, 0, 0), (10, 10, 10));
-        assert_eq!(convert(4, 0, 0), (13, 13, 13));
-        assert_eq!(convert(10, 0, 0), (26, 27, 27));
+        assert_eq!(convert(4, 0, 0), (14, 14, 14));
+        assert_eq!(convert(10, 0, 0), (27, 27, 27));
         assert_eq!(convert(25, 0, 0), (59, 59, 59));
         assert_eq!(convert(50, 0, 0), (119, 119, 119));
         assert_eq!(convert(75, 0, 0), (185, 185, 185));
-        assert_eq!(convert(90, 0, 0), (226, 227, 227));
+        assert_eq!(convert(90, 0, 0), (227, 227, 227));
         assert_eq!(convert(97, 0, 0), (247, 247, 247));
         assert_eq!(convert(98, 0, 0), (249, 250, 250));
         assert_eq!(convert(99, 0, 0), (252, 253, 253));
@@ -227,18 +613,100 @@ mod tests {
 
     #[test]
     fn test_color() {
-        assert_eq!(convert(3, -3, 0), (1, 12, 10));
-        assert_eq!(convert(10, 0, 10), (31, 26, 10));
+        assert_eq!(convert(3, -3, 0), (3, 13, 10));
+        assert_eq!(convert(10, 0, 10), (32, 27, 11));
         assert_eq!(convert(70, 40, 0), (239, 143, 173));
         assert_eq!(convert(70, 25, -30), (190, 158, 226));
-        assert_eq!(convert(40, -40, 40), (34, 108, 18));
-        assert_eq!(convert(72, 0, -42), (117, 180, 253));
+        assert_eq!(convert(40, -40, 40), (34, 109, 19));
+        assert_eq!(convert(72, 0, -42), (117, 181, 253));
+    }
+
+    #[test]
+    fn test_to_lab() {
+        assert_eq!(to_lab(0, 0, 0), (0, 0, 0));
+        assert_eq!(to_lab(255, 255, 255), (100, 0, 0));
+        assert_eq!(to_lab(119, 119, 119), (50, 0, 0));
+        // Round trips of the colors in `test_color`.
+        assert_eq!(to_lab(239, 143, 173), (70, 40, 0));
+        assert_eq!(to_lab(190, 158, 226), (70, 25, -30));
+        assert_eq!(to_lab(34, 109, 19), (40, -40, 40));
+        assert_eq!(to_lab(117, 181, 253), (72, 0, -42));
+        // Dark colors lose precision in sRGB, so only round trip to within 1.
+        assert_eq!(to_lab(3, 13, 10), (3, -3, 0));
+        assert_eq!(to_lab(32, 27, 11), (10, -1, 10));
+    }
+
+    #[test]
+    fn test_gamma_inv() {
+        // Black and white convert to the ends of the linear range, and the
+        // other colors to the middle of the range they cover.
+        assert_eq!(gamma_inv(0), 0);
+        assert_eq!(gamma_inv(255), LINEAR_RGB_TO_SRGB[255]);
+        let (lo, hi) = (LINEAR_RGB_TO_SRGB[1], LINEAR_RGB_TO_SRGB[2]);
+        assert_eq!(gamma_inv(1), (lo + hi) / 2);
+        for u in 0..=255 {
+            let linear = gamma_inv(u);
+            assert_eq!(gamma(linear), Ok(u));
+        }
+    }
+
+    #[test]
+    fn test_lab16() {
+        let lab = ColorLab { l: 50, a: 0, b: 0 };
+        let gray = ColorLab16 {
+            l: 12800,
+            a: 0,
+            b: 0,
+        };
+        assert_eq!(ColorLab16::from(lab), gray);
+        assert_eq!(ColorLab::from(gray), lab);
+        let lab16 = ColorLab16 {
+            l: 12928,
+            a: -128,
+            b: 383,
+        };
+        assert_eq!(ColorLab::from(lab16), ColorLab { l: 51, a: 0, b: 1 });
+
+        assert_eq!(
+            gray.to_srgb16(),
+            Ok(ColorRgb16 {
+                r: 30650,
+                g: 30686,
+                b: 30691
+            })
+        );
+        assert_eq!(
+            ColorRgb::from(gray.to_srgb16().unwrap()),
+            lab.to_srgb_clamped()
+        );
+        // Every step of 1/256 in lightness changes the output.
+        let mut prev = gray.to_srgb16().unwrap();
+        for l in 12801..=13056 {
+            let color = ColorLab16 { l, a: 0, b: 0 }.to_srgb16().unwrap();
+            assert!(color.g > prev.g);
+            prev = color;
+        }
+
+        let red = ColorLab16 {
+            l: 512,
+            a: 5120,
+            b: 5120,
+        };
+        assert!(!red.is_valid());
+        assert_eq!(
+            red.to_srgb16_clamped(),
+            ColorRgb16 {
+                r: 11305,
+                g: 0,
+                b: 0
+            }
+        );
     }
 
     #[test]
     fn test_clamp() {
         assert_eq!(clamp(110, 0, 0), (255, 255, 255));
-        assert_eq!(clamp(2, 20, 20), (42, 0, 0));
+        assert_eq!(clamp(2, 20, 20), (43, 0, 0));
         assert_eq!(clamp(100, -100, 50), (0, 255, 151));
         // Make sure that extreme values don't cause under/overflow errors.
         clamp(-128, -128, -128);
@@ -251,6 +719,156 @@ mod tests {
         clamp(127, 127, 127);
     }
 
+    #[test]
+    fn test_lch() {
+        let lab = ColorLab {
+            l: 50,
+            a: 30,
+            b: 40,
+        };
+        let lch = ColorLch {
+            l: 50,
+            c: 50,
+            h: 53,
+        };
+        assert_eq!(lab.to_lch(), lch);
+        assert_eq!(
+            lch.to_lab(),
+            ColorLab {
+                l: 50,
+                a: 30,
+                b: 39
+            }
+        );
+        let lab = ColorLab {
+            l: 20,
+            a: -20,
+            b: 0,
+        };
+        assert_eq!(
+            lab.to_lch(),
+            ColorLch {
+                l: 20,
+                c: 20,
+                h: 180
+            }
+        );
+
+        // Saturated colors keep their chroma, past what fits in an `i8`.
+        let blue = ColorRgb { r: 0, g: 0, b: 255 }.to_lab();
+        assert_eq!(
+            blue,
+            ColorLab {
+                l: 32,
+                a: 79,
+                b: -107
+            }
+        );
+        assert_eq!(blue.to_lch().c, 133);
+        // The primaries round trip to within 1.
+        let primaries = [(255, 0, 0), (0, 255, 0), (0, 0, 255)];
+        for &(r, g, b) in &primaries {
+            let lab = ColorRgb { r, g, b }.to_lab();
+            let round_trip = lab.to_lch().to_lab();
+            assert_eq!(round_trip.l, lab.l);
+            assert!((round_trip.a - lab.a).abs() <= 1, "{:?}", (r, g, b));
+            assert!((round_trip.b - lab.b).abs() <= 1, "{:?}", (r, g, b));
+        }
+
+        let lch = ColorLch { l: 70, c: 40, h: 0 };
+        assert_eq!(lch.to_lab(), ColorLab { l: 70, a: 40, b: 0 });
+        let lch = lch.rotate_hue(90);
+        assert_eq!(lch.to_lab(), ColorLab { l: 70, a: 0, b: 40 });
+        let lch = lch.rotate_hue(-180);
+        assert_eq!(lch.h, 270);
+        assert_eq!(
+            lch.to_lab(),
+            ColorLab {
+                l: 70,
+                a: 0,
+                b: -40
+            }
+        );
+        assert_eq!(lch.rotate_hue(100).h, 10);
+
+        // The largest circle around gray at l=70 has radius 39.
+        let lch = ColorLch {
+            l: 70,
+            c: 100,
+            h: 0,
+        };
+        assert_eq!(lch.clamp_chroma().c, 39);
+        assert!(lch.clamp_chroma().to_lab().is_valid());
+        let lch = ColorLch { l: 70, c: 20, h: 0 };
+        assert_eq!(lch.clamp_chroma(), lch);
+
+        let lab = ColorLab {
+            l: 70,
+            a: 10,
+            b: 10,
+        };
+        assert_eq!(
+            lab.shift(20, 90),
+            ColorLab {
+                l: 70,
+                a: 10,
+                b: 30
+            }
+        );
+        assert_eq!(
+            lab.shift(20, 180),
+            ColorLab {
+                l: 70,
+                a: -10,
+                b: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_mapped() {
+        let mapped = |l, a, b| {
+            let ColorRgb { r, g, b } = ColorLab { l, a, b }.to_srgb_mapped();
+            (r, g, b)
+        };
+        assert_eq!(mapped(70, 40, 0), convert(70, 40, 0));
+        // Clamping makes this pure red, but it's an orangey red.
+        assert_eq!(clamp(2, 20, 20), (43, 0, 0));
+        assert_eq!(mapped(2, 20, 20), (17, 4, 0));
+        assert_eq!(mapped(40, -100, 100), (19, 110, 3));
+        // Colors that are too light or dark become white or black.
+        assert_eq!(mapped(110, 50, 50), (255, 255, 255));
+        assert_eq!(mapped(-10, 50, 50), (0, 0, 0));
+    }
+
+    // Compared with published blackbody color tables for sRGB. All within a
+    // few /256 from 1900K up.
+
+    #[test]
+    fn test_kelvin() {
+        let kelvin = |k| {
+            let ColorRgb { r, g, b } = ColorRgb::from_kelvin(k);
+            (r, g, b)
+        };
+        assert_eq!(kelvin(1900), (255, 132, 0));
+        assert_eq!(kelvin(2700), (255, 172, 88));
+        assert_eq!(kelvin(4000), (255, 211, 165));
+        assert_eq!(kelvin(6500), (255, 248, 254));
+        assert_eq!(kelvin(10000), (204, 217, 255));
+        assert_eq!(kelvin(12000), (192, 208, 255));
+        assert_eq!(kelvin(20000), kelvin(12000));
+        assert_eq!(kelvin(0), kelvin(1000));
+
+        let lab = |k| {
+            let ColorLab { l, a, b } = ColorLab::from_kelvin(k, 70);
+            (l, a, b)
+        };
+        assert_eq!(lab(2700), (70, 21, 50));
+        // D65 is close to, but not quite on, the Planckian locus.
+        assert_eq!(lab(6500), (70, 2, -1));
+        assert_eq!(lab(12000), (70, 4, -21));
+    }
+
     #[test]
     fn test_lab_radius() {
         assert_eq!(radius(0, 0, 0), 0);
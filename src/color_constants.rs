@@ -1,3 +1,5 @@
+use crate::gamma::gamma_curve;
+
 // CIE-lab delta, scaled by 12 bits.
 pub const DELTA: i32 = (6 << 12) / 29;
 
@@ -10,6 +12,27 @@ pub const D65_ILLUMINANT: [i32; 3] = [95, 100, 109];
 pub const XYZ_TO_RGB_LINEAR: [[i32; 3]; 3] =
     [[13273, -6296, -2042], [-3969, 7683, 170], [228, -836, 4329]];
 
+// The conversion table from linear RGB to CIE-XYZ, scaled by 12 bits. This is
+// the inverse of `XYZ_TO_RGB_LINEAR`.
+//     https://en.wikipedia.org/wiki/SRGB
+pub const RGB_LINEAR_TO_XYZ: [[i32; 3]; 3] =
+    [[1689, 1465, 739], [871, 2930, 296], [79, 489, 3894]];
+
+// The conversion tables between linear RGB and Oklab, through the LMS cone
+// responses, scaled by 12 bits.
+//     https://bottosson.github.io/posts/oklab/
+pub const RGB_LINEAR_TO_LMS: [[i32; 3]; 3] =
+    [[1688, 2197, 211], [868, 2788, 440], [362, 1154, 2580]];
+pub const LMS_TO_OKLAB: [[i32; 3]; 3] =
+    [[862, 3251, -17], [8102, -9948, 1846], [106, 3206, -3312]];
+pub const OKLAB_TO_LMS: [[i32; 3]; 3] =
+    [[4096, 1623, 884], [4096, -432, -262], [4096, -367, -5290]];
+pub const LMS_TO_RGB_LINEAR: [[i32; 3]; 3] = [
+    [16698, -13548, 946],
+    [-5196, 10690, -1398],
+    [-17, -2881, 6994],
+];
+
 // Lookup table for converting between linear RGB and sRGB.
 // For L in range [0, 100] and R,G,B in range [0, 256].
 // Input scaled by 12 bits.
@@ -62,6 +85,11 @@ pub const GAMMA_CORRECTION: [u8; 256] = [
     236, 239, 241, 244, 247, 249, 252, 255,
 ];
 
+/// Like `GAMMA_CORRECTION`, but with 16 bits of output precision, so that dim
+/// colors don't all round down to zero. Rounds to `GAMMA_CORRECTION` when
+/// scaled back down to 8 bits.
+pub const GAMMA_CORRECTION_16: [u16; 256] = gamma_curve(2.8);
+
 pub const MAX_LAB_RADIUS: [[[i8; 22]; 22]; 12] = [
     [
         [
@@ -0,0 +1,263 @@
+use crate::color::{ColorLab, ColorRgb};
+use crate::int_math::div_round;
+
+/// [HSV](https://en.wikipedia.org/wiki/HSL_and_HSV) colors, as used by
+/// design tools and most other LED libraries. All components range from 0 to
+/// 255: `h` goes once around the color wheel (so 85 is green and 171 is blue),
+/// and `s` and `v` are fractions of 255.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorHsv {
+    pub h: u8,
+    pub s: u8,
+    pub v: u8,
+}
+
+/// [HSL](https://en.wikipedia.org/wiki/HSL_and_HSV) colors. All components
+/// range from 0 to 255, with `h` as in `ColorHsv`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorHsl {
+    pub h: u8,
+    pub s: u8,
+    pub l: u8,
+}
+
+impl ColorHsv {
+    /// Convert to sRGB.
+    pub fn to_rgb(self) -> ColorRgb {
+        let chroma = div255(self.v as u32 * self.s as u32);
+        hue_to_rgb(self.h, chroma, self.v as u32 - chroma)
+    }
+
+    /// Convert to sRGB, using a hue wheel that gives orange and yellow the
+    /// same share as the other colors, like FastLED's "rainbow". It's made to
+    /// be fast on small microcontrollers: it only uses 8-bit multiplies and
+    /// shifts.
+    pub fn to_rgb_rainbow(self) -> ColorRgb {
+        // Each eighth of the wheel blends between two of red, orange, yellow,
+        // green, aqua, blue, purple, and pink, in that order.
+        let offset = (self.h & 0x1F) << 3;
+        let third = scale8(offset, 85);
+        let two_thirds = scale8(offset, 170);
+        let (r, g, b) = match self.h >> 5 {
+            0 => (255 - third, third, 0),
+            1 => (171, 85 + third, 0),
+            2 => (171 - two_thirds, 170 + third, 0),
+            3 => (0, 255 - third, third),
+            4 => (0, 171 - two_thirds, 85 + two_thirds),
+            5 => (third, 0, 255 - third),
+            6 => (85 + third, 0, 171 - third),
+            _ => (170 + third, 0, 85 - third),
+        };
+        // Desaturate towards white, then dim.
+        let white = 255 - self.s;
+        let channel = |c: u8| scale8(scale8(c, self.s) + white, self.v);
+        ColorRgb {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+        }
+    }
+
+    /// Convert to CIE-LAB, via sRGB.
+    pub fn to_lab(self) -> ColorLab {
+        self.to_rgb().to_lab()
+    }
+}
+
+impl ColorHsl {
+    /// Convert to sRGB.
+    pub fn to_rgb(self) -> ColorRgb {
+        let l = self.l as u32;
+        let range = 255 - (2 * l as i32 - 255).unsigned_abs();
+        let chroma = div255(range * self.s as u32);
+        hue_to_rgb(self.h, chroma, l - chroma.div_ceil(2))
+    }
+
+    /// Convert to CIE-LAB, via sRGB.
+    pub fn to_lab(self) -> ColorLab {
+        self.to_rgb().to_lab()
+    }
+}
+
+impl ColorRgb {
+    /// Convert to HSV.
+    pub fn to_hsv(self) -> ColorHsv {
+        let (max, min) = max_min(self);
+        ColorHsv {
+            h: hue(self),
+            s: if max == 0 {
+                0
+            } else {
+                div_round(((max - min) * 255).into(), max.into()) as u8
+            },
+            v: max as u8,
+        }
+    }
+
+    /// Convert to HSL.
+    pub fn to_hsl(self) -> ColorHsl {
+        let (max, min) = max_min(self);
+        let range = 255 - (max as i32 + min as i32 - 255).unsigned_abs();
+        ColorHsl {
+            h: hue(self),
+            s: if max == min {
+                0
+            } else {
+                div_round(((max - min) * 255).into(), range.into()) as u8
+            },
+            l: div_round((max + min).into(), 2) as u8,
+        }
+    }
+}
+
+// Make a color of hue `h`, whose largest channel is `chroma` more than its
+// smallest channel, `min`.
+fn hue_to_rgb(h: u8, chroma: u32, min: u32) -> ColorRgb {
+    // Which sixth of the wheel the hue is in, and how far through it.
+    let sixths = h as u32 * 6;
+    let rising = min + ((chroma * (sixths & 0xFF) + 128) >> 8);
+    let falling = 2 * min + chroma - rising;
+    let max = min + chroma;
+    let (r, g, b) = match sixths >> 8 {
+        0 => (max, rising, min),
+        1 => (falling, max, min),
+        2 => (min, max, rising),
+        3 => (min, falling, max),
+        4 => (rising, min, max),
+        _ => (max, min, falling),
+    };
+    ColorRgb {
+        r: r as u8,
+        g: g as u8,
+        b: b as u8,
+    }
+}
+
+// The hue of a color, as in `ColorHsv`. Grays have hue 0.
+fn hue(rgb: ColorRgb) -> u8 {
+    let (r, g, b) = (rgb.r as i32, rgb.g as i32, rgb.b as i32);
+    let (max, min) = max_min(rgb);
+    let delta = (max - min) as i32;
+    if delta == 0 {
+        return 0;
+    }
+    // Which sixth of the wheel the hue is in, and how far from its middle.
+    let (sixth, diff) = if max as i32 == r {
+        (0, g - b)
+    } else if max as i32 == g {
+        (2, b - r)
+    } else {
+        (4, r - g)
+    };
+    let h = ((sixth * delta + diff) * 256 + 3 * delta) / (6 * delta);
+    h.rem_euclid(256) as u8
+}
+
+fn max_min(rgb: ColorRgb) -> (u32, u32) {
+    let ColorRgb { r, g, b } = rgb;
+    (r.max(g).max(b) as u32, r.min(g).min(b) as u32)
+}
+
+// `x / 255`, rounded, for `x` up to 255 * 255.
+fn div255(x: u32) -> u32 {
+    (x + 128 + ((x + 128) >> 8)) >> 8
+}
+
+// Scale `x` by `scale / 256`, with 255 leaving `x` unchanged.
+fn scale8(x: u8, scale: u8) -> u8 {
+    ((x as u16 * (scale as u16 + 1)) >> 8) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hsv(h: u8, s: u8, v: u8) -> (u8, u8, u8) {
+        let ColorRgb { r, g, b } = ColorHsv { h, s, v }.to_rgb();
+        (r, g, b)
+    }
+
+    fn hsl(h: u8, s: u8, l: u8) -> (u8, u8, u8) {
+        let ColorRgb { r, g, b } = ColorHsl { h, s, l }.to_rgb();
+        (r, g, b)
+    }
+
+    fn rainbow(h: u8, s: u8, v: u8) -> (u8, u8, u8) {
+        let ColorRgb { r, g, b } = ColorHsv { h, s, v }.to_rgb_rainbow();
+        (r, g, b)
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert_eq!(hsv(0, 0, 0), (0, 0, 0));
+        assert_eq!(hsv(123, 0, 200), (200, 200, 200));
+        assert_eq!(hsv(0, 255, 255), (255, 0, 0));
+        assert_eq!(hsv(64, 255, 255), (127, 255, 0));
+        assert_eq!(hsv(128, 255, 255), (0, 255, 255));
+        assert_eq!(hsv(128, 128, 100), (50, 100, 100));
+        assert_eq!(hsv(192, 255, 255), (128, 0, 255));
+        assert_eq!(hsv(255, 255, 255), (255, 0, 6));
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_eq!(hsl(0, 0, 0), (0, 0, 0));
+        assert_eq!(hsl(0, 255, 255), (255, 255, 255));
+        // Pure colors have a lightness of 127.5, which can't be represented.
+        assert_eq!(hsl(0, 255, 128), (255, 1, 1));
+        assert_eq!(hsl(0, 255, 127), (254, 0, 0));
+        assert_eq!(hsl(128, 255, 64), (0, 128, 128));
+        assert_eq!(hsl(128, 128, 192), (160, 223, 223));
+    }
+
+    #[test]
+    fn test_rgb_to_hsv() {
+        let colors = [
+            (0, 0, 0),
+            (255, 255, 255),
+            (255, 0, 0),
+            (0, 255, 255),
+            (50, 100, 100),
+            (160, 223, 223),
+            (128, 0, 255),
+        ];
+        for &(r, g, b) in &colors {
+            let rgb = ColorRgb { r, g, b };
+            assert_eq!(rgb.to_hsv().to_rgb(), rgb);
+            // HSL can only round trip colors with a whole lightness.
+            let (max, min) = max_min(rgb);
+            if (max + min) % 2 == 0 {
+                assert_eq!(rgb.to_hsl().to_rgb(), rgb);
+            }
+        }
+        let rgb = ColorRgb { r: 0, g: 255, b: 0 };
+        assert_eq!(
+            rgb.to_hsv(),
+            ColorHsv {
+                h: 85,
+                s: 255,
+                v: 255
+            }
+        );
+        assert_eq!(
+            rgb.to_hsl(),
+            ColorHsl {
+                h: 85,
+                s: 255,
+                l: 128
+            }
+        );
+    }
+
+    #[test]
+    fn test_rainbow() {
+        assert_eq!(rainbow(0, 255, 255), (255, 0, 0));
+        assert_eq!(rainbow(32, 255, 255), (171, 85, 0));
+        assert_eq!(rainbow(64, 255, 255), (171, 170, 0));
+        assert_eq!(rainbow(96, 255, 255), (0, 255, 0));
+        assert_eq!(rainbow(160, 255, 255), (0, 0, 255));
+        assert_eq!(rainbow(0, 0, 255), (255, 255, 255));
+        assert_eq!(rainbow(96, 255, 128), (0, 128, 0));
+        assert_eq!(rainbow(0, 0, 0), (0, 0, 0));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::color::{
+    linear_rgb_to_srgb, srgb_to_linear_rgb, ColorLinearRgb, ColorRgb,
+};
+use crate::color_constants::*;
+use crate::int_math::{atan2, cos, icbrt, isqrt_round, sin};
+
+/// [Oklab](https://bottosson.github.io/posts/oklab/) colors. Like CIE-LAB, but
+/// more perceptually uniform: in particular, blues don't drift towards purple
+/// as their lightness or chroma changes. All components are scaled by 100, so
+/// `l` ranges from 0 to 100, and `a` and `b` stay within about -40 to 40.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorOklab {
+    pub l: i8,
+    pub a: i8,
+    pub b: i8,
+}
+
+/// Oklab colors in polar coordinates, like `ColorLch` is for `ColorLab`. `c`
+/// is scaled by 100, and `h` is the hue in degrees, counterclockwise from the
+/// `a` axis.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorOklch {
+    pub l: i8,
+    pub c: u8,
+    pub h: i16,
+}
+
+impl ColorOklab {
+    /// Convert to sRGB. If this color is invalid, return an `Err` of a clamped
+    /// version of it.
+    pub fn to_srgb(self) -> Result<ColorRgb, ColorRgb> {
+        linear_rgb_to_srgb(oklab_to_linear_rgb(self))
+    }
+
+    /// Convert to sRGB. If this color is invalid, automatically clamp it to a
+    /// valid color.
+    pub fn to_srgb_clamped(self) -> ColorRgb {
+        match self.to_srgb() {
+            Ok(color) => color,
+            Err(color) => color,
+        }
+    }
+
+    /// Check if this is a valid (i.e., representable) color.
+    pub fn is_valid(self) -> bool {
+        self.to_srgb().is_ok()
+    }
+
+    /// Compute the radius of the largest valid a,b circle centered at this
+    /// color. Unlike `ColorLab::max_radius`, this searches rather than using a
+    /// table, so avoid calling it on every frame.
+    pub fn max_radius(self) -> i8 {
+        let fits = |radius: i32| {
+            (0..360).step_by(10).all(|deg| {
+                ColorOklab {
+                    l: self.l,
+                    a: self.a.saturating_add(cos(deg, radius) as i8),
+                    b: self.b.saturating_add(sin(deg, radius) as i8),
+                }
+                .is_valid()
+            })
+        };
+        let mut radius = 0;
+        while fits(radius + 1) {
+            radius += 1;
+        }
+        radius as i8
+    }
+
+    /// Convert to polar coordinates.
+    pub fn to_oklch(self) -> ColorOklch {
+        let (a, b) = (self.a as i32, self.b as i32);
+        ColorOklch {
+            l: self.l,
+            c: isqrt_round((a * a + b * b) as u64) as u8,
+            h: atan2(b, a) as i16,
+        }
+    }
+}
+
+impl ColorOklch {
+    /// Convert to rectangular coordinates.
+    pub fn to_oklab(self) -> ColorOklab {
+        let (c, h) = (self.c as i32, self.h as i32);
+        ColorOklab {
+            l: self.l,
+            a: cos(h, c).clamp(-128, 127) as i8,
+            b: sin(h, c).clamp(-128, 127) as i8,
+        }
+    }
+
+    /// Convert to sRGB, clamping it to a valid color.
+    pub fn to_srgb_clamped(self) -> ColorRgb {
+        self.to_oklab().to_srgb_clamped()
+    }
+
+    /// Rotate the hue by `degrees`, counterclockwise. The result's hue is
+    /// between 0 and 359.
+    pub fn rotate_hue(self, degrees: i16) -> ColorOklch {
+        ColorOklch {
+            h: (self.h as i32 + degrees as i32).rem_euclid(360) as i16,
+            ..self
+        }
+    }
+}
+
+impl ColorRgb {
+    /// Convert to Oklab.
+    pub fn to_oklab(self) -> ColorOklab {
+        linear_rgb_to_oklab(srgb_to_linear_rgb(self))
+    }
+}
+
+// Multiply a vector by a matrix scaled by 12 bits.
+fn transform(t: [[i32; 3]; 3], v: [i64; 3]) -> [i64; 3] {
+    let row = |i: usize| {
+        (t[i][0] as i64 * v[0] + t[i][1] as i64 * v[1] + t[i][2] as i64 * v[2])
+            >> 12
+    };
+    [row(0), row(1), row(2)]
+}
+
+// Convert from Oklab to linear RGB, described here:
+//    https://bottosson.github.io/posts/oklab/
+// Output scaled by 12 bits, and by 100 to match `xyz_to_linear_rgb`.
+fn oklab_to_linear_rgb(oklab: ColorOklab) -> ColorLinearRgb {
+    let ColorOklab { l, a, b } = oklab;
+    let unscale = |v: i8| ((v as i64) << 12) / 100;
+    let lms = transform(OKLAB_TO_LMS, [unscale(l), unscale(a), unscale(b)]);
+    let cube = |v: i64| (((v * v) >> 12) * v) >> 12;
+    let rgb = transform(
+        LMS_TO_RGB_LINEAR,
+        [cube(lms[0]), cube(lms[1]), cube(lms[2])],
+    );
+    let scale = |v: i64| (v * 100).clamp(i32::MIN as i64, i32::MAX as i64);
+    ColorLinearRgb {
+        r: scale(rgb[0]) as i32,
+        g: scale(rgb[1]) as i32,
+        b: scale(rgb[2]) as i32,
+    }
+}
+
+// Convert from linear RGB to Oklab, the inverse of `oklab_to_linear_rgb`.
+fn linear_rgb_to_oklab(rgb: ColorLinearRgb) -> ColorOklab {
+    let ColorLinearRgb { r, g, b } = rgb;
+    let lms = transform(RGB_LINEAR_TO_LMS, [r as i64, g as i64, b as i64]);
+    // The cube root, scaled by 14 bits rather than 12 for precision. The
+    // input is scaled by 100 as well as 12 bits.
+    let root = |v: i64| icbrt(((v.max(0) as u64) << 30) / 100) as i64;
+    let lab =
+        transform(LMS_TO_OKLAB, [root(lms[0]), root(lms[1]), root(lms[2])]);
+    // Scale from 14 bits to hundredths, rounding.
+    let unscale = |v: i64| ((v * 100 + (1 << 13)) >> 14) as i32;
+    ColorOklab {
+        l: unscale(lab[0]).clamp(0, 100) as i8,
+        a: unscale(lab[1]).clamp(-128, 127) as i8,
+        b: unscale(lab[2]).clamp(-128, 127) as i8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(l: i8, a: i8, b: i8) -> (u8, u8, u8) {
+        let ColorRgb { r, g, b } = ColorOklab { l, a, b }.to_srgb().unwrap();
+        (r, g, b)
+    }
+
+    fn to_oklab(r: u8, g: u8, b: u8) -> (i8, i8, i8) {
+        let ColorOklab { l, a, b } = ColorRgb { r, g, b }.to_oklab();
+        (l, a, b)
+    }
+
+    // Tested against https://bottosson.github.io/posts/oklab/
+    // (floating point). All within 1/256.
+
+    #[test]
+    fn test_oklab() {
+        assert_eq!(convert(0, 0, 0), (0, 0, 0));
+        assert_eq!(convert(57, 0, 0), (119, 119, 119));
+        assert_eq!(convert(70, 10, 5), (220, 130, 121));
+        assert_eq!(convert(60, -5, -10), (59, 135, 190));
+        assert_eq!(convert(40, 5, -10), (78, 55, 124));
+        assert_eq!(convert(80, -10, 10), (150, 210, 114));
+        assert!(!ColorOklab {
+            l: 90,
+            a: 0,
+            b: -30
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn test_to_oklab() {
+        assert_eq!(to_oklab(0, 0, 0), (0, 0, 0));
+        assert_eq!(to_oklab(255, 255, 255), (100, 0, 0));
+        assert_eq!(to_oklab(255, 0, 0), (63, 22, 13));
+        assert_eq!(to_oklab(0, 255, 0), (86, -23, 18));
+        assert_eq!(to_oklab(0, 0, 255), (45, -3, -31));
+        assert_eq!(to_oklab(119, 119, 119), (57, 0, 0));
+    }
+
+    #[test]
+    fn test_max_radius() {
+        let gray = ColorOklab { l: 70, a: 0, b: 0 };
+        let radius = gray.max_radius() as u8;
+        assert_eq!(radius, 12);
+        let shifted = |deg, r| {
+            ColorOklch {
+                l: 70,
+                c: r,
+                h: deg,
+            }
+            .to_oklab()
+        };
+        for deg in (0..360).step_by(10) {
+            assert!(shifted(deg, radius).is_valid());
+        }
+        assert!((0..360).any(|deg| !shifted(deg, radius + 1).is_valid()));
+        // Make sure that extreme values don't cause under/overflow errors.
+        let max_radius = |l, a, b| ColorOklab { l, a, b }.max_radius();
+        assert_eq!(max_radius(50, 127, 0), 0);
+        assert_eq!(max_radius(50, -128, 127), 0);
+        assert_eq!(max_radius(127, 127, 127), 0);
+        assert_eq!(max_radius(-128, -128, -128), 0);
+    }
+
+    #[test]
+    fn test_oklch() {
+        let oklab = ColorOklab {
+            l: 45,
+            a: -3,
+            b: -31,
+        };
+        let oklch = ColorOklch {
+            l: 45,
+            c: 31,
+            h: 264,
+        };
+        assert_eq!(oklab.to_oklch(), oklch);
+        // Within 1.
+        assert_eq!(
+            oklch.to_oklab(),
+            ColorOklab {
+                l: 45,
+                a: -3,
+                b: -30
+            }
+        );
+        assert_eq!(oklch.rotate_hue(100).h, 4);
+    }
+}
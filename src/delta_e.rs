@@ -0,0 +1,275 @@
+use crate::color::ColorLab;
+use crate::int_math::{
+    atan2_precise, cos_precise, div_round, isqrt, isqrt_round, sin_precise,
+    DEG, ONE,
+};
+
+// Inside this module, L, a, b and differences are scaled by 10000, so that
+// the CIEDE2000 reference data can be used exactly. Fractions (sines, cosines,
+// and other factors) are scaled by `ONE`, and angles by `DEG`, as in
+// `int_math`'s precise trigonometry.
+const SCALE: i64 = 10000;
+
+impl ColorLab {
+    /// The CIE76 color difference: the straight-line distance between two
+    /// colors, in hundredths. A difference of about 230 is just noticeable,
+    /// but CIE76 overstates the differences between saturated colors.
+    pub fn delta_e76(self, other: ColorLab) -> u32 {
+        let [dl, da, db] = diff(scale(self), scale(other));
+        round(isqrt((dl * dl + da * da + db * db) as u64) as i64)
+    }
+
+    /// The CIE94 color difference (with the graphic arts weights), in
+    /// hundredths. It treats `self` as the reference color, so swapping the
+    /// colors can give a slightly different result.
+    pub fn delta_e94(self, other: ColorLab) -> u32 {
+        round(cie94(scale(self), scale(other)))
+    }
+
+    /// The CIEDE2000 color difference, in hundredths. This is the most
+    /// perceptually accurate of the three: a difference of about 100 is just
+    /// noticeable.
+    pub fn delta_e2000(self, other: ColorLab) -> u32 {
+        round(ciede2000(scale(self), scale(other)))
+    }
+}
+
+fn scale(lab: ColorLab) -> [i64; 3] {
+    [
+        lab.l as i64 * SCALE,
+        lab.a as i64 * SCALE,
+        lab.b as i64 * SCALE,
+    ]
+}
+
+fn diff(lab1: [i64; 3], lab2: [i64; 3]) -> [i64; 3] {
+    [lab2[0] - lab1[0], lab2[1] - lab1[1], lab2[2] - lab1[2]]
+}
+
+// Round from the module's scale to hundredths.
+fn round(x: i64) -> u32 {
+    ((x + SCALE / 200) / (SCALE / 100)) as u32
+}
+
+fn chroma(a: i64, b: i64) -> i64 {
+    sqrt_round(a * a + b * b)
+}
+
+fn sqrt_round(x: i64) -> i64 {
+    isqrt_round(x as u64) as i64
+}
+
+// CIE94, described here:
+//    https://en.wikipedia.org/wiki/Color_difference#CIE94
+fn cie94(lab1: [i64; 3], lab2: [i64; 3]) -> i64 {
+    let [dl, da, db] = diff(lab1, lab2);
+    let c1 = chroma(lab1[1], lab1[2]);
+    let dc = chroma(lab2[1], lab2[2]) - c1;
+    // The hue difference, squared. Rounding can make it slightly negative.
+    let dh2 = (da * da + db * db - dc * dc).max(0);
+    let sc = SCALE + 45 * c1 / 1000;
+    let sh = SCALE + 15 * c1 / 1000;
+    let c = dc * SCALE / sc;
+    let h2 = dh2 * SCALE / sh * SCALE / sh;
+    isqrt((dl * dl + c * c + h2) as u64) as i64
+}
+
+// CIEDE2000, described here (and with the reference data used in the tests):
+//    http://www2.ece.rochester.edu/~gsharma/ciede2000/
+fn ciede2000(lab1: [i64; 3], lab2: [i64; 3]) -> i64 {
+    let [l1, a1, b1] = lab1;
+    let [l2, a2, b2] = lab2;
+
+    // Stretch the a axis for colors near gray.
+    let c_mean = (chroma(a1, b1) + chroma(a2, b2)) / 2;
+    let g = (ONE - chroma_weight(c_mean)) / 2;
+    let a1 = div_round(a1 * (ONE + g), ONE);
+    let a2 = div_round(a2 * (ONE + g), ONE);
+    let (c1, c2) = (chroma(a1, b1), chroma(a2, b2));
+    let (h1, h2) = (atan2_precise(b1, a1), atan2_precise(b2, a2));
+    let gray = c1 == 0 || c2 == 0;
+
+    let dl = l2 - l1;
+    let dc = c2 - c1;
+    let mut dh = h2 - h1;
+    if gray {
+        dh = 0;
+    } else if dh > 180 * DEG {
+        dh -= 360 * DEG;
+    } else if dh < -180 * DEG {
+        dh += 360 * DEG;
+    }
+    let dh = div_round(2 * sqrt_round(c1 * c2) * sin_precise(dh / 2), ONE);
+
+    let l_mean = (l1 + l2) / 2;
+    let c_mean = (c1 + c2) / 2;
+    let h_mean = if gray {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180 * DEG {
+        (h1 + h2) / 2
+    } else if h1 + h2 < 360 * DEG {
+        (h1 + h2 + 360 * DEG) / 2
+    } else {
+        (h1 + h2 - 360 * DEG) / 2
+    };
+
+    // The coefficients are 0.17, 0.24, 0.32 and 0.20, scaled by 30 bits.
+    let t = ONE - 182536110 * cos_precise(h_mean - 30 * DEG) / ONE
+        + 257698038 * cos_precise(2 * h_mean) / ONE
+        + 343597384 * cos_precise(3 * h_mean + 6 * DEG) / ONE
+        - 214748365 * cos_precise(4 * h_mean - 63 * DEG) / ONE;
+    // Rotate the weights for blues, by up to 30 degrees around a hue of 275.
+    // `z` is scaled by 20 bits, so `z * z` needs shifting down by 10.
+    let z = (h_mean - 275 * DEG) / 25;
+    let rotation = (30 * exp_neg((z * z) >> 10)) >> 10;
+    let rt = -sin_precise(2 * rotation) * 2 * chroma_weight(c_mean) / ONE;
+
+    // The weights are scaled by 30 bits, since they divide the differences.
+    let l50 = (l_mean - 50 * SCALE).abs();
+    let sq = isqrt((20 * SCALE * SCALE + l50 * l50) as u64) as i64;
+    let sl = ONE + 15 * l50 * (l50 * ONE / sq) / (1000 * SCALE);
+    let sc = ONE + 45 * c_mean * ONE / (1000 * SCALE);
+    let sh = ONE + 15 * c_mean * t / (1000 * SCALE);
+
+    let l = div_round(dl * ONE, sl);
+    let c = div_round(dc * ONE, sc);
+    let h = div_round(dh * ONE, sh);
+    let sum = l * l + c * c + h * h + div_round(c * rt, ONE) * h;
+    sqrt_round(sum.max(0))
+}
+
+// `sqrt(c^7 / (c^7 + 25^7))`, which is near 0 for grays and near 1 for
+// saturated colors.
+fn chroma_weight(c: i64) -> i64 {
+    let c25 = 25 * SCALE;
+    // Raise the smaller of c / 25 or 25 / c to the 7th, so that it's at most
+    // one.
+    let r = (c.min(c25) << 30) / c.max(c25);
+    let mut p = ONE;
+    for _ in 0..7 {
+        p = (p * r) >> 30;
+    }
+    let ratio = if c < c25 {
+        (p << 30) / (p + ONE)
+    } else {
+        (ONE << 30) / (p + ONE)
+    };
+    isqrt((ratio << 30) as u64) as i64
+}
+
+// `e^-x`, with `x` and the result scaled by 30 bits.
+fn exp_neg(x: i64) -> i64 {
+    if x > 30 * ONE {
+        return 0;
+    }
+    // `e^-x = (e^(-x/32))^32`, and the Taylor series converges quickly for
+    // `x/32`.
+    let x = x >> 5;
+    let (mut sum, mut term) = (ONE, ONE);
+    for n in 1..12 {
+        term = -term * x / ONE / n;
+        sum += term;
+    }
+    for _ in 0..5 {
+        sum = (sum * sum) >> 30;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lab(l: i8, a: i8, b: i8) -> ColorLab {
+        ColorLab { l, a, b }
+    }
+
+    // From Sharma, Wu, and Dalal, "The CIEDE2000 Color-Difference Formula:
+    // Implementation Notes, Supplementary Test Data, and Mathematical
+    // Observations". Each row is L, a, b of both colors, then the difference,
+    // all scaled by 10000.
+    const SHARMA: [[i64; 7]; 34] = [
+        [500000, 26772, -797751, 500000, 0, -827485, 20425],
+        [500000, 31571, -772803, 500000, 0, -827485, 28615],
+        [500000, 28361, -740200, 500000, 0, -827485, 34412],
+        [500000, -13802, -842814, 500000, 0, -827485, 10000],
+        [500000, -11848, -848006, 500000, 0, -827485, 10000],
+        [500000, -9009, -855211, 500000, 0, -827485, 10000],
+        [500000, 0, 0, 500000, -10000, 20000, 23669],
+        [500000, -10000, 20000, 500000, 0, 0, 23669],
+        [500000, 24900, -10, 500000, -24900, 9, 71792],
+        [500000, 24900, -10, 500000, -24900, 10, 71792],
+        [500000, 24900, -10, 500000, -24900, 11, 72195],
+        [500000, 24900, -10, 500000, -24900, 12, 72195],
+        [500000, -10, 24900, 500000, 9, -24900, 48045],
+        [500000, -10, 24900, 500000, 10, -24900, 48045],
+        [500000, -10, 24900, 500000, 11, -24900, 47461],
+        [500000, 25000, 0, 500000, 0, -25000, 43065],
+        [500000, 25000, 0, 730000, 250000, -180000, 271492],
+        [500000, 25000, 0, 610000, -50000, 290000, 228977],
+        [500000, 25000, 0, 560000, -270000, -30000, 319030],
+        [500000, 25000, 0, 580000, 240000, 150000, 194535],
+        [500000, 25000, 0, 500000, 31736, 5854, 10000],
+        [500000, 25000, 0, 500000, 32972, 0, 10000],
+        [500000, 25000, 0, 500000, 18634, 5757, 10000],
+        [500000, 25000, 0, 500000, 32592, 3350, 10000],
+        [602574, -340099, 362677, 604626, -341751, 394387, 12644],
+        [630109, -310961, -58663, 628187, -297946, -40864, 12630],
+        [612901, 37196, -53901, 614292, 22480, -49620, 18731],
+        [350831, -441164, 37933, 350232, -400716, 15901, 18645],
+        [227233, 200904, -466940, 230331, 149730, -425619, 20373],
+        [364612, 478580, 183852, 362715, 505065, 212231, 14146],
+        [908027, -20831, 14410, 911528, -16435, 447, 14441],
+        [909257, -5406, -9208, 886381, -8985, -7239, 15381],
+        [67747, -2908, -24247, 58714, -985, -22286, 6377],
+        [20776, 795, -11350, 9033, -636, -5514, 9082],
+    ];
+
+    #[test]
+    fn test_ciede2000() {
+        for row in &SHARMA {
+            let lab1 = [row[0], row[1], row[2]];
+            let lab2 = [row[3], row[4], row[5]];
+            // Within 0.0001 of the (rounded) reference, and symmetric.
+            let delta = ciede2000(lab1, lab2);
+            assert!((delta - row[6]).abs() <= 1, "{:?}: {}", row, delta);
+            assert_eq!(ciede2000(lab2, lab1), delta);
+        }
+    }
+
+    #[test]
+    fn test_delta_e() {
+        let gray = lab(50, 0, 0);
+        assert_eq!(gray.delta_e76(gray), 0);
+        assert_eq!(gray.delta_e94(gray), 0);
+        assert_eq!(gray.delta_e2000(gray), 0);
+
+        let a = lab(50, 30, 40);
+        let b = lab(52, 33, 36);
+        assert_eq!(a.delta_e76(b), 539);
+        assert_eq!(b.delta_e76(a), 539);
+        assert_eq!(a.delta_e94(b), 344);
+        assert_eq!(b.delta_e94(a), 347);
+        assert_eq!(a.delta_e2000(b), 386);
+        assert_eq!(b.delta_e2000(a), 386);
+
+        let blue = lab(30, 20, -60);
+        let purple = lab(30, 10, -60);
+        assert_eq!(blue.delta_e76(purple), 1000);
+        assert_eq!(blue.delta_e2000(purple), 658);
+
+        // Make sure that extreme values don't cause overflow errors.
+        let extremes = [-128, 0, 127];
+        for &l in &extremes {
+            for &a in &extremes {
+                for &b in &extremes {
+                    let color = lab(l, a, b);
+                    let other = lab(-l.max(-127), -a.max(-127), b);
+                    color.delta_e76(other);
+                    color.delta_e94(other);
+                    color.delta_e2000(other);
+                }
+            }
+        }
+    }
+}
@@ -1,40 +1,693 @@
-use crate::ColorRgb;
+use crate::power_limit::{scale_color, FULL_SCALE};
+use crate::{
+    Calibration, ColorOrder, ColorRgb, ColorRgb16, Gamma, LightStrip,
+    PowerLimit,
+};
 
+use core::cmp;
 use embedded_hal::blocking::spi;
 
 const START_FRAME: [u8; 4] = [0, 0, 0, 0];
 
-pub struct DotstarStrip<SPI: spi::Write<u8>> {
+/// The largest value of the APA102's 5-bit global brightness, at which the
+/// LEDs are driven at full current.
+pub const MAX_BRIGHTNESS: u8 = 31;
+
+/// The most bytes sent to show `num_lights` lights, for any `Chip`. A
+/// `DotstarStrip` with a buffer at least this big sends each frame in a single
+/// SPI write.
+pub const fn frame_len(num_lights: usize) -> usize {
+    let apa102 = Chip::Apa102.frame_len(num_lights);
+    let sk9822 = Chip::Sk9822.frame_len(num_lights);
+    if apa102 > sk9822 {
+        apa102
+    } else {
+        sk9822
+    }
+}
+
+/// The LED chips that speak the APA102 protocol. They differ in how a frame
+/// needs to be ended.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Chip {
+    Apa102,
+    /// Uses the same protocol as the `Apa102`.
+    Apa102c,
+    /// Only updates its LEDs (including their global brightness) once it
+    /// sees a "reset frame" of 32 zero bits, so that's sent after the LED
+    /// frames.
+    Sk9822,
+    /// Uses the same protocol as the `Apa102`, at higher clock speeds.
+    Hd107s,
+}
+
+impl Chip {
+    /// The number of bytes sent to show `num_lights` lights.
+    pub const fn frame_len(self, num_lights: usize) -> usize {
+        START_FRAME.len() + 4 * num_lights + self.end_frame_len(num_lights)
+    }
+
+    // The number of zero bytes sent after the LED frames. Each LED delays
+    // the data by half a clock cycle, so the end frame needs to provide at
+    // least `num_lights / 2` extra clock edges. For the APA102, send twice
+    // that to be safe.
+    const fn end_frame_len(self, num_lights: usize) -> usize {
+        match self {
+            Chip::Apa102 | Chip::Apa102c | Chip::Hd107s => {
+                num_lights.div_ceil(8)
+            }
+            Chip::Sk9822 => RESET_FRAME_LEN + num_lights.div_ceil(16),
+        }
+    }
+}
+
+const RESET_FRAME_LEN: usize = 4;
+
+/// A strip of APA102 ("DotStar") LEDs, driven over SPI.
+///
+/// If `N` is nonzero, each frame is assembled in an `N` byte buffer and sent
+/// in as few SPI writes as possible. Otherwise each LED is written
+/// separately.
+///
+/// If `D` is nonzero, the first `D` lights are dithered over time: after gamma
+/// correction, colors have more precision than the LEDs' 8 bits, and what's
+/// lost by rounding one frame is carried over to the next. Shown steadily,
+/// the LEDs then average out to the precise color, which smooths out dim
+/// colors and slow fades. This needs frames to be sent often (e.g. every few
+/// milliseconds), or it flickers.
+pub struct DotstarStrip<
+    SPI: spi::Write<u8>,
+    const N: usize = 0,
+    const D: usize = 0,
+> {
     spi_bus: SPI,
+    encoding: Encoding,
+    chip: Chip,
+    power_limit: Option<PowerLimit>,
+    // The estimated current of the last frame, before and after limiting it.
+    requested_microamps: u32,
+    estimated_microamps: u32,
+    buffer: [u8; N],
+    // For each dithered light, how much of each channel is left over from
+    // earlier frames, out of 257 (one step in 8 bits).
+    dither: [[u16; 3]; D],
+}
+
+// The settings that control how each LED frame is made.
+#[derive(Clone, Copy)]
+struct Encoding {
+    brightness: u8,
+    color_order: ColorOrder,
+    calibration: Calibration,
+    gamma: Gamma,
 }
 
 impl<SPI: spi::Write<u8>> DotstarStrip<SPI> {
     pub fn new(spi_bus: SPI) -> Self {
-        Self { spi_bus }
+        Self::new_buffered(spi_bus)
     }
+}
 
+impl<SPI: spi::Write<u8>, const N: usize, const D: usize>
+    DotstarStrip<SPI, N, D>
+{
+    /// Make a strip that sends frames from an `N` byte buffer. Use
+    /// `frame_len` to find how big it needs to be to send every frame at
+    /// once, e.g. `DotstarStrip::<_, { frame_len(300) }>::new_buffered(spi)`.
+    /// To dither the lights as well, set `D` to how many there are, e.g.
+    /// `DotstarStrip::<_, 0, 300>::new_buffered(spi)`.
+    pub fn new_buffered(spi_bus: SPI) -> Self {
+        Self {
+            spi_bus,
+            encoding: Encoding {
+                brightness: MAX_BRIGHTNESS,
+                color_order: ColorOrder::Bgr,
+                calibration: Calibration::default(),
+                gamma: Gamma::default(),
+            },
+            chip: Chip::Apa102,
+            power_limit: None,
+            requested_microamps: 0,
+            estimated_microamps: 0,
+            buffer: [0; N],
+            // Start halfway through a step, so that the first frame is
+            // rounded to the nearest value.
+            dither: [[128; 3]; D],
+        }
+    }
+
+    /// Give back the SPI bus.
+    pub fn release(self) -> SPI {
+        self.spi_bus
+    }
+
+    /// Send the colors to the strip.
+    #[deprecated(note = "use `LightStrip::show` instead")]
     pub fn send(&mut self, lights: &[ColorRgb]) -> Result<(), SPI::Error> {
-        // TODO combine into fewer write calls? does it matter?
-        self.spi_bus.write(&START_FRAME)?;
-        for light in lights {
-            self.spi_bus.write(&led_frame(*light))?;
+        self.show(lights)
+    }
+
+    /// The global brightness of the whole strip, from 0 to `MAX_BRIGHTNESS`.
+    pub fn brightness(&self) -> u8 {
+        self.encoding.brightness
+    }
+
+    /// Set the global brightness of the whole strip, from 0 to
+    /// `MAX_BRIGHTNESS` (larger values are clamped). Unlike darkening the
+    /// colors themselves, this lowers the LED current, so dim scenes keep the
+    /// full 8-bit color resolution.
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.encoding.brightness = cmp::min(brightness, MAX_BRIGHTNESS);
+    }
+
+    /// The order the strip expects each LED's color channels to be sent in.
+    pub fn color_order(&self) -> ColorOrder {
+        self.encoding.color_order
+    }
+
+    /// Set the order to send each LED's color channels in. APA102s use
+    /// `ColorOrder::Bgr` (the default), but some clones differ.
+    pub fn set_color_order(&mut self, color_order: ColorOrder) {
+        self.encoding.color_order = color_order;
+    }
+
+    /// The calibration applied to colors before they're gamma corrected.
+    pub fn calibration(&self) -> Calibration {
+        self.encoding.calibration
+    }
+
+    /// Set the calibration applied to colors before they're gamma corrected,
+    /// to make this strip match others. The default leaves colors unchanged.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.encoding.calibration = calibration;
+    }
+
+    /// The gamma correction applied to colors before they're sent.
+    pub fn gamma(&self) -> Gamma {
+        self.encoding.gamma
+    }
+
+    /// Set the gamma correction applied to colors before they're sent. The
+    /// default is `Gamma::default()`, which matches `ColorRgb::correct_gamma`.
+    pub fn set_gamma(&mut self, gamma: Gamma) {
+        self.encoding.gamma = gamma;
+    }
+
+    /// Which kind of LEDs are in the strip.
+    pub fn chip(&self) -> Chip {
+        self.chip
+    }
+
+    /// Set which kind of LEDs are in the strip (the default is
+    /// `Chip::Apa102`). This controls how frames are ended.
+    pub fn set_chip(&mut self, chip: Chip) {
+        self.chip = chip;
+    }
+
+    /// The limit on how much current the strip may draw, if any.
+    pub fn power_limit(&self) -> Option<PowerLimit> {
+        self.power_limit
+    }
+
+    /// Limit how much current the strip may draw. Frames that would go over
+    /// the limit are dimmed to fit. The default is no limit.
+    pub fn set_power_limit(&mut self, power_limit: Option<PowerLimit>) {
+        self.power_limit = power_limit;
+    }
+
+    /// The estimated current that the last frame would have drawn, before
+    /// it was limited, in milliamps. Only estimated if there's a power limit.
+    pub fn requested_milliamps(&self) -> u32 {
+        self.requested_microamps / 1000
+    }
+
+    /// The estimated current drawn by the last frame, in milliamps. Only
+    /// estimated if there's a power limit.
+    pub fn estimated_milliamps(&self) -> u32 {
+        self.estimated_microamps / 1000
+    }
+
+    /// Like `show`, but also give each light its own global brightness, from
+    /// 0 to `MAX_BRIGHTNESS`. These are scaled by the strip's brightness.
+    /// Lights past the end of `brightness` use `MAX_BRIGHTNESS`.
+    pub fn show_with_brightness(
+        &mut self,
+        lights: &[ColorRgb],
+        brightness: &[u8],
+    ) -> Result<(), SPI::Error> {
+        let strip_brightness = self.encoding.brightness as u32;
+        let pixels = lights.iter().enumerate().map(|(i, light)| {
+            let pixel = brightness.get(i).copied().unwrap_or(MAX_BRIGHTNESS);
+            let pixel = cmp::min(pixel, MAX_BRIGHTNESS) as u32;
+            let max = MAX_BRIGHTNESS as u32;
+            let combined = (pixel * strip_brightness + max / 2) / max;
+            (ColorRgb16::from(*light), combined as u8)
+        });
+        self.send_pixels(pixels, true, Encoding::led_frame)
+    }
+
+    /// Like `show`, but for 16-bit colors, e.g. from `ColorLab16::to_srgb16`
+    /// for fades that are smoother than `ColorLab` allows. Each light's global
+    /// brightness is chosen along with its color, to get as much precision as
+    /// possible out of dim colors. The strip's brightness still scales the
+    /// result. Lights aren't dithered, since picking their brightness already
+    /// gives dim colors enough precision.
+    pub fn show_hd(&mut self, lights: &[ColorRgb16]) -> Result<(), SPI::Error> {
+        let brightness = self.encoding.brightness;
+        let pixels = lights.iter().map(|light| (*light, brightness));
+        self.send_pixels(pixels, false, Encoding::hd_led_frame)
+    }
+
+    // Send a color and global brightness for each LED. The colors are
+    // corrected, limited to the power budget, dithered (if `dither` is set),
+    // and then made into LED frames by `led_frame`.
+    fn send_pixels<I, F>(
+        &mut self,
+        pixels: I,
+        dither: bool,
+        led_frame: F,
+    ) -> Result<(), SPI::Error>
+    where
+        I: ExactSizeIterator<Item = (ColorRgb16, u8)> + Clone,
+        F: Fn(Encoding, ColorRgb16, u8) -> [u8; 4],
+    {
+        let encoding = self.encoding;
+        let scale = match self.power_limit {
+            None => FULL_SCALE,
+            Some(limit) => {
+                let len = pixels.len();
+                let requested = pixels
+                    .clone()
+                    .map(|(light, brightness)| {
+                        limit.microamps(encoding.correct(light), brightness)
+                    })
+                    .fold(0, u32::saturating_add);
+                let scale = limit.scale(requested, len);
+                self.requested_microamps = requested;
+                self.estimated_microamps =
+                    limit.scaled_microamps(requested, len, scale);
+                scale
+            }
+        };
+        let errors = &mut self.dither;
+        let frames = pixels.enumerate().map(|(i, (light, brightness))| {
+            let mut light = scale_color(encoding.correct(light), scale);
+            if let (true, Some(error)) = (dither, errors.get_mut(i)) {
+                light = dither_color(light, error);
+            }
+            led_frame(encoding, light, brightness)
+        });
+        let len = frames.len();
+        let mut writer = FrameWriter {
+            spi_bus: &mut self.spi_bus,
+            buffer: &mut self.buffer,
+            len: 0,
+        };
+        writer.write(&START_FRAME)?;
+        for frame in frames {
+            writer.write(&frame)?;
         }
 
         // End frame:
-        for _ in 0..ceiling(lights.len(), 8) {
-            self.spi_bus.write(&[0])?;
+        for _ in 0..self.chip.end_frame_len(len) {
+            writer.write(&[0])?;
+        }
+        writer.flush()
+    }
+}
+
+impl<SPI: spi::Write<u8>, const N: usize, const D: usize> LightStrip
+    for DotstarStrip<SPI, N, D>
+{
+    type Error = SPI::Error;
+
+    fn show(&mut self, lights: &[ColorRgb]) -> Result<(), SPI::Error> {
+        let brightness = self.encoding.brightness;
+        let pixels = lights
+            .iter()
+            .map(|light| (ColorRgb16::from(*light), brightness));
+        self.send_pixels(pixels, true, Encoding::led_frame)
+    }
+}
+
+// Collects bytes in a buffer, and writes them to the SPI bus whenever it
+// fills up. With an empty buffer, bytes are written immediately.
+struct FrameWriter<'a, SPI: spi::Write<u8>> {
+    spi_bus: &'a mut SPI,
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a, SPI: spi::Write<u8>> FrameWriter<'a, SPI> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), SPI::Error> {
+        if self.buffer.is_empty() {
+            return self.spi_bus.write(bytes);
+        }
+        for byte in bytes {
+            if self.len == self.buffer.len() {
+                self.flush()?;
+            }
+            self.buffer[self.len] = *byte;
+            self.len += 1;
         }
         Ok(())
     }
+
+    fn flush(&mut self) -> Result<(), SPI::Error> {
+        if self.len > 0 {
+            self.spi_bus.write(&self.buffer[..self.len])?;
+            self.len = 0;
+        }
+        Ok(())
+    }
+}
+
+// Round each channel of a color down to 8 bits (as a multiple of 257), adding
+// on `error`, what was left over from earlier frames, and saving what's left
+// over from this one.
+fn dither_color(light: ColorRgb16, error: &mut [u16; 3]) -> ColorRgb16 {
+    let mut channel = |c: u16, i: usize| {
+        let total = c as u32 + error[i] as u32;
+        error[i] = (total % 257) as u16;
+        (total / 257 * 257) as u16
+    };
+    ColorRgb16 {
+        r: channel(light.r, 0),
+        g: channel(light.g, 1),
+        b: channel(light.b, 2),
+    }
 }
 
-fn led_frame(light: ColorRgb) -> [u8; 4] {
-    let light = light.correct_gamma();
-    let prefix_and_global_brightness = 255;
-    [prefix_and_global_brightness, light.b, light.g, light.r]
+// Each LED frame starts with three set bits, followed by the 5-bit global
+// brightness.
+const LED_FRAME_PREFIX: u8 = 0b1110_0000;
+
+impl Encoding {
+    // Calibrate and then gamma correct a color.
+    fn correct(self, light: ColorRgb16) -> ColorRgb16 {
+        self.gamma.correct(self.calibration.apply(light))
+    }
+
+    // Make an LED frame from a corrected color.
+    fn led_frame(self, light: ColorRgb16, brightness: u8) -> [u8; 4] {
+        let light = ColorRgb::from(light);
+        let prefix_and_global_brightness = LED_FRAME_PREFIX | brightness;
+        let [c0, c1, c2] = self.color_order.arrange(light.r, light.g, light.b);
+        [prefix_and_global_brightness, c0, c1, c2]
+    }
+
+    // The light given off by a channel is proportional to `brightness * pwm`,
+    // so the same color can be made at many different brightnesses. Pick the
+    // smallest brightness at which the brightest channel still fits in 8
+    // bits, leaving as much precision as possible for the PWM values.
+    // `strip_brightness` scales the result.
+    fn hd_led_frame(self, light: ColorRgb16, strip_brightness: u8) -> [u8; 4] {
+        let strip_brightness = strip_brightness as u32;
+        let brightest = cmp::max(light.r, cmp::max(light.g, light.b)) as u32;
+        let brightness = (brightest * strip_brightness).div_ceil(65535);
+        if brightness == 0 {
+            return [LED_FRAME_PREFIX, 0, 0, 0];
+        }
+        // 65535 = 255 * 257, so this is the fraction of full PWM needed to
+        // produce `channel` at `brightness`, out of 255.
+        let divisor = brightness * 257;
+        let pwm = |channel: u16| {
+            let pwm =
+                (channel as u32 * strip_brightness + divisor / 2) / divisor;
+            cmp::min(pwm, 255) as u8
+        };
+        let (r, g, b) = (pwm(light.r), pwm(light.g), pwm(light.b));
+        let [c0, c1, c2] = self.color_order.arrange(r, g, b);
+        [LED_FRAME_PREFIX | brightness as u8, c0, c1, c2]
+    }
 }
 
-fn ceiling(num: usize, divisor: usize) -> usize {
-    // Ceiling of integer division
-    (num + divisor - 1) / divisor
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_spi::MockSpi;
+
+    fn send(lights: &[ColorRgb]) -> MockSpi {
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.show(lights).unwrap();
+        strip.spi_bus
+    }
+
+    #[test]
+    fn test_frames() {
+        let red = ColorRgb { r: 255, g: 0, b: 0 };
+        let green = ColorRgb { r: 0, g: 255, b: 0 };
+        let blue = ColorRgb { r: 0, g: 0, b: 255 };
+        assert_eq!(
+            send(&[red, green, blue]).bytes,
+            [
+                0, 0, 0, 0, // start frame
+                255, 0, 0, 255, // red
+                255, 0, 255, 0, // green
+                255, 255, 0, 0, // blue
+                0, // end frame
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_send() {
+        let lights = [ColorRgb { r: 255, g: 0, b: 0 }];
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.send(&lights).unwrap();
+        assert_eq!(strip.release().bytes, send(&lights).bytes);
+    }
+
+    #[test]
+    fn test_gamma() {
+        let gray = ColorRgb {
+            r: 128,
+            g: 128,
+            b: 128,
+        };
+        assert_eq!(send(&[gray]).bytes, [0, 0, 0, 0, 255, 37, 37, 37, 0]);
+    }
+
+    #[test]
+    fn test_end_frame() {
+        assert_eq!(send(&[]).bytes, [0, 0, 0, 0]);
+        let lights = [ColorRgb::black(); 9];
+        let bytes = send(&lights).bytes;
+        assert_eq!(bytes.len(), 4 + 9 * 4 + 2);
+        assert_eq!(bytes[4..8], [255, 0, 0, 0]);
+        assert_eq!(bytes[40..], [0, 0]);
+    }
+
+    #[test]
+    fn test_brightness() {
+        let white = ColorRgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_brightness(100);
+        assert_eq!(strip.brightness(), MAX_BRIGHTNESS);
+        strip.set_brightness(3);
+        strip.show(&[white]).unwrap();
+        assert_eq!(strip.spi_bus.bytes[4..8], [0b1110_0011, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_pixel_brightness() {
+        let lights = [ColorRgb::black(); 3];
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.show_with_brightness(&lights, &[0, 31]).unwrap();
+        assert_eq!(strip.spi_bus.bytes[4], 0b1110_0000);
+        assert_eq!(strip.spi_bus.bytes[8], 0b1111_1111);
+        assert_eq!(strip.spi_bus.bytes[12], 0b1111_1111);
+
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_brightness(16);
+        strip.show_with_brightness(&lights, &[31, 16, 1]).unwrap();
+        assert_eq!(strip.spi_bus.bytes[4], 0b1110_0000 | 16);
+        assert_eq!(strip.spi_bus.bytes[8], 0b1110_0000 | 8);
+        assert_eq!(strip.spi_bus.bytes[12], 0b1110_0001);
+    }
+
+    #[test]
+    fn test_hd() {
+        let white = ColorRgb16 {
+            r: 65535,
+            g: 65535,
+            b: 65535,
+        };
+        // Gamma corrects to 53, which is zero in 8 bits.
+        let dim = ColorRgb16 {
+            r: 20 * 257,
+            g: 0,
+            b: 0,
+        };
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.show_hd(&[white, dim, ColorRgb16::black()]).unwrap();
+        let bytes = &strip.spi_bus.bytes;
+        assert_eq!(bytes[4..8], [0b1111_1111, 255, 255, 255]);
+        assert_eq!(bytes[8..12], [0b1110_0001, 0, 0, 6]);
+        assert_eq!(bytes[12..16], [0b1110_0000, 0, 0, 0]);
+
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_brightness(16);
+        strip.show_hd(&[white]).unwrap();
+        assert_eq!(strip.spi_bus.bytes[4..8], [0b1111_0000, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_buffered() {
+        let lights = [
+            ColorRgb { r: 1, g: 2, b: 3 },
+            ColorRgb { r: 128, g: 0, b: 0 },
+            ColorRgb { r: 0, g: 0, b: 255 },
+        ];
+        let unbuffered = send(&lights);
+        assert_eq!(unbuffered.writes, 5);
+
+        let mut strip = DotstarStrip::<_, { frame_len(3) }>::new_buffered(
+            MockSpi::default(),
+        );
+        strip.show(&lights).unwrap();
+        assert_eq!(strip.spi_bus.writes, 1);
+        assert_eq!(strip.spi_bus.bytes, unbuffered.bytes);
+
+        let mut strip = DotstarStrip::<_, 6>::new_buffered(MockSpi::default());
+        strip.show(&lights).unwrap();
+        assert_eq!(strip.spi_bus.writes, 3);
+        assert_eq!(strip.spi_bus.bytes, unbuffered.bytes);
+    }
+
+    #[test]
+    fn test_color_order() {
+        let light = ColorRgb {
+            r: 255,
+            g: 128,
+            b: 0,
+        };
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_color_order(ColorOrder::Grb);
+        strip.show(&[light]).unwrap();
+        strip.show_hd(&[light.into()]).unwrap();
+        let bytes = &strip.spi_bus.bytes;
+        assert_eq!(bytes[4..8], [255, 37, 255, 0]);
+        assert_eq!(bytes[13..17], [255, 37, 255, 0]);
+    }
+
+    #[test]
+    fn test_chip() {
+        let lights = [ColorRgb::black(); 20];
+        for chip in [Chip::Apa102, Chip::Apa102c, Chip::Hd107s] {
+            let mut strip = DotstarStrip::new(MockSpi::default());
+            strip.set_chip(chip);
+            strip.show(&lights).unwrap();
+            assert_eq!(strip.spi_bus.bytes.len(), 4 + 20 * 4 + 3);
+            assert_eq!(strip.spi_bus.bytes.len(), chip.frame_len(20));
+        }
+
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_chip(Chip::Sk9822);
+        strip.show(&lights).unwrap();
+        let bytes = &strip.spi_bus.bytes;
+        assert_eq!(bytes.len(), 4 + 20 * 4 + 4 + 2);
+        assert_eq!(bytes.len(), Chip::Sk9822.frame_len(20));
+        assert_eq!(bytes[84..], [0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(frame_len(20), Chip::Sk9822.frame_len(20));
+        assert_eq!(frame_len(200), Chip::Apa102.frame_len(200));
+    }
+
+    #[test]
+    fn test_dither() {
+        // Green gamma corrects to 53, which is zero in 8 bits.
+        let light = ColorRgb {
+            r: 255,
+            g: 20,
+            b: 0,
+        };
+        let mut strip =
+            DotstarStrip::<_, 0, 1>::new_buffered(MockSpi::default());
+        let mut green = 0;
+        for i in 0..257 {
+            strip.show(&[light, light]).unwrap();
+            let bytes = &strip.spi_bus.bytes[i * 13..];
+            assert_eq!(bytes[4..8], [255, 0, bytes[6], 255]);
+            green += bytes[6] as u32;
+            // Only the first light is dithered.
+            assert_eq!(bytes[8..12], [255, 0, 0, 255]);
+        }
+        // Averaged over time, the green is 53 / 257.
+        assert_eq!(green, 53);
+        assert_eq!(strip.spi_bus.bytes[6], 0);
+    }
+
+    #[test]
+    fn test_set_gamma() {
+        let gray = ColorRgb {
+            r: 128,
+            g: 128,
+            b: 128,
+        };
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_gamma(Gamma::Off);
+        strip.show(&[gray]).unwrap();
+        assert_eq!(strip.spi_bus.bytes[4..8], [255, 128, 128, 128]);
+
+        static LINEAR: [u16; 256] = crate::gamma_curve(1.0);
+        static STEEP: [u16; 256] = crate::gamma_curve(4.0);
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_gamma(Gamma::PerChannel(&LINEAR, &STEEP, &LINEAR));
+        strip.show(&[gray]).unwrap();
+        assert_eq!(strip.spi_bus.bytes[4..8], [255, 128, 16, 128]);
+    }
+
+    #[test]
+    fn test_calibration() {
+        let white = ColorRgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_gamma(Gamma::Off);
+        strip.set_calibration(Calibration::from_gain(4096, 2048, 1024));
+        strip.show(&[white]).unwrap();
+        assert_eq!(strip.spi_bus.bytes[4..8], [255, 64, 127, 255]);
+    }
+
+    #[test]
+    fn test_power_limit() {
+        let white = ColorRgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let lights = [white; 10];
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_gamma(Gamma::Off);
+        strip.set_power_limit(Some(PowerLimit::new(1000)));
+        strip.show(&lights).unwrap();
+        assert_eq!(strip.requested_milliamps(), 610);
+        assert_eq!(strip.estimated_milliamps(), 610);
+        assert_eq!(strip.spi_bus.bytes[4..8], [255, 255, 255, 255]);
+
+        // 10mA idle, so there's 300mA left of the 600mA needed.
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_gamma(Gamma::Off);
+        strip.set_power_limit(Some(PowerLimit::new(310)));
+        strip.show(&lights).unwrap();
+        assert_eq!(strip.requested_milliamps(), 610);
+        assert_eq!(strip.estimated_milliamps(), 310);
+        assert_eq!(strip.spi_bus.bytes[4..8], [255, 127, 127, 127]);
+
+        // Dim LEDs draw less.
+        let mut strip = DotstarStrip::new(MockSpi::default());
+        strip.set_gamma(Gamma::Off);
+        strip.set_power_limit(Some(PowerLimit::new(310)));
+        strip.show_with_brightness(&lights, &[15; 10]).unwrap();
+        assert_eq!(strip.requested_milliamps(), 300);
+        assert_eq!(strip.spi_bus.bytes[4..8], [0b1110_1111, 255, 255, 255]);
+    }
 }
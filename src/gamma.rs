@@ -0,0 +1,182 @@
+use crate::color::ColorRgb16;
+use crate::color_constants::GAMMA_CORRECTION_16;
+
+use core::f64::consts::LN_2;
+
+/// How to gamma correct colors before displaying them on LEDs (see
+/// `ColorRgb::correct_gamma`). Curves are tables of 16-bit outputs for 256
+/// evenly spaced inputs, like `GAMMA_CORRECTION_16`, and can be made with
+/// `gamma_curve`. Inputs between the table entries are interpolated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gamma {
+    /// Leave colors unchanged, e.g. because they're already gamma corrected.
+    Off,
+    /// Use the same curve for every channel.
+    Curve(&'static [u16; 256]),
+    /// Use a separate curve for each of the red, green, and blue channels.
+    PerChannel(
+        &'static [u16; 256],
+        &'static [u16; 256],
+        &'static [u16; 256],
+    ),
+}
+
+impl Gamma {
+    /// Apply this gamma correction to a color.
+    pub fn correct(self, color: ColorRgb16) -> ColorRgb16 {
+        let ColorRgb16 { r, g, b } = color;
+        match self {
+            Gamma::Off => color,
+            Gamma::Curve(curve) => ColorRgb16 {
+                r: interpolate(curve, r),
+                g: interpolate(curve, g),
+                b: interpolate(curve, b),
+            },
+            Gamma::PerChannel(r_curve, g_curve, b_curve) => ColorRgb16 {
+                r: interpolate(r_curve, r),
+                g: interpolate(g_curve, g),
+                b: interpolate(b_curve, b),
+            },
+        }
+    }
+}
+
+impl Default for Gamma {
+    fn default() -> Gamma {
+        Gamma::Curve(&GAMMA_CORRECTION_16)
+    }
+}
+
+/// Compute a gamma correction curve, raising each input (from 0 to 1) to the
+/// power of `exponent`. This is meant to be run at compile time, e.g.:
+///
+/// `static GREEN_CURVE: [u16; 256] = gamma_curve(3.0);`
+pub const fn gamma_curve(exponent: f64) -> [u16; 256] {
+    let mut curve = [0; 256];
+    let mut i = 1;
+    while i < 256 {
+        let x = i as f64 / 255.0;
+        curve[i] = (exp(exponent * ln(x)) * 65535.0 + 0.5) as u16;
+        i += 1;
+    }
+    curve
+}
+
+// Linearly interpolate between the entries of a curve. The input is scaled so
+// that 65535 lands on the last entry.
+fn interpolate(curve: &[u16; 256], u: u16) -> u16 {
+    let i = (u / 257) as usize;
+    let frac = (u % 257) as i32;
+    if i == 255 {
+        return curve[255];
+    }
+    let lo = curve[i] as i32;
+    let hi = curve[i + 1] as i32;
+    (lo + ((hi - lo) * frac + 128) / 257) as u16
+}
+
+// The natural log of `x`, for `x > 0`. Writing `x = m * 2^e` with `m` in
+// [1, 2), ln(x) = e*ln(2) + ln(m), and ln(m) = 2*atanh((m-1)/(m+1)) converges
+// quickly as a power series.
+const fn ln(x: f64) -> f64 {
+    let mut m = x;
+    let mut e = 0;
+    while m < 1.0 {
+        m *= 2.0;
+        e -= 1;
+    }
+    while m >= 2.0 {
+        m /= 2.0;
+        e += 1;
+    }
+    let z = (m - 1.0) / (m + 1.0);
+    let mut term = z;
+    let mut sum = 0.0;
+    let mut k = 1;
+    while k < 40 {
+        sum += term / k as f64;
+        term *= z * z;
+        k += 2;
+    }
+    e as f64 * LN_2 + 2.0 * sum
+}
+
+// e to the power of `x`. Writing `x = k*ln(2) + r` with `|r| < ln(2)`,
+// e^x = 2^k * e^r, and e^r converges quickly as a power series.
+const fn exp(x: f64) -> f64 {
+    let k = (x / LN_2) as i32;
+    let r = x - k as f64 * LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    let mut n = 1;
+    while n < 30 {
+        term *= r / n as f64;
+        sum += term;
+        n += 1;
+    }
+    let mut i = 0;
+    while i < k {
+        sum *= 2.0;
+        i += 1;
+    }
+    while i > k {
+        sum /= 2.0;
+        i -= 1;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorRgb;
+
+    fn correct(gamma: Gamma, u: u16) -> u16 {
+        gamma.correct(ColorRgb16 { r: u, g: u, b: u }).r
+    }
+
+    #[test]
+    fn test_gamma_curve() {
+        let curve = gamma_curve(2.8);
+        assert_eq!(curve[0..8], [0, 0, 0, 0, 1, 1, 2, 3]);
+        assert_eq!(curve[128], 9514);
+        let linear = gamma_curve(1.0);
+        for (i, value) in linear.iter().enumerate() {
+            assert_eq!(*value, i as u16 * 257);
+        }
+        let curve = gamma_curve(2.2);
+        assert_eq!(curve[128], 14386);
+        assert_eq!(curve[255], 65535);
+    }
+
+    #[test]
+    fn test_gamma() {
+        for i in 0..=255 {
+            let color = ColorRgb { r: i, g: i, b: i };
+            let wide = Gamma::default().correct(color.into());
+            assert_eq!(wide.r, GAMMA_CORRECTION_16[i as usize]);
+            assert_eq!(ColorRgb::from(wide), color.correct_gamma());
+        }
+        // Halfway between the table entries for 20 and 21.
+        assert_eq!(correct(Gamma::default(), 20 * 257 + 128), 56);
+        // Dim values that `GAMMA_CORRECTION` would round to zero.
+        assert_eq!(correct(Gamma::default(), 20 * 257), 53);
+        assert_eq!(correct(Gamma::Off, 20 * 257), 20 * 257);
+
+        static LINEAR: [u16; 256] = gamma_curve(1.0);
+        let gamma = Gamma::PerChannel(&LINEAR, &GAMMA_CORRECTION_16, &LINEAR);
+        let color = ColorRgb16 {
+            r: 1000,
+            g: 1000,
+            b: 1000,
+        };
+        assert_eq!(
+            gamma.correct(color),
+            ColorRgb16 {
+                r: 1000,
+                g: 1,
+                b: 1000
+            }
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use crate::color::{
+    linear_rgb_to_srgb, srgb_to_linear_rgb, ColorLab, ColorLab16, ColorLch,
+    ColorLinearRgb, ColorRgb,
+};
+use crate::color_oklab::{ColorOklab, ColorOklch};
+use crate::int_math::mix;
+
+/// Colors that can be blended together. Which color type is used decides how
+/// the blend looks: `ColorLab` and `ColorOklab` blend evenly in perceived
+/// lightness, `ColorLch` and `ColorOklch` keep the chroma up by going around
+/// the hue wheel, and `ColorRgb` blends in linear light, like mixing lights.
+pub trait Lerp: Copy {
+    /// Blend from `self` (when `t` is 0) to `other` (when `t` is 255).
+    fn lerp(self, other: Self, t: u8) -> Self;
+}
+
+impl Lerp for ColorLab {
+    fn lerp(self, other: ColorLab, t: u8) -> ColorLab {
+        ColorLab {
+            l: mix_i8(self.l, other.l, t),
+            a: mix_i8(self.a, other.a, t),
+            b: mix_i8(self.b, other.b, t),
+        }
+    }
+}
+
+impl Lerp for ColorLab16 {
+    fn lerp(self, other: ColorLab16, t: u8) -> ColorLab16 {
+        let mix_i16 = |a: i16, b: i16| mix(a as i32, b as i32, t) as i16;
+        ColorLab16 {
+            l: mix_i16(self.l, other.l),
+            a: mix_i16(self.a, other.a),
+            b: mix_i16(self.b, other.b),
+        }
+    }
+}
+
+impl Lerp for ColorLch {
+    /// Hues take the shortest way around the hue wheel.
+    fn lerp(self, other: ColorLch, t: u8) -> ColorLch {
+        ColorLch {
+            l: mix_i8(self.l, other.l, t),
+            c: mix_u8(self.c, other.c, t),
+            h: mix_hue(self.h, other.h, t),
+        }
+    }
+}
+
+impl Lerp for ColorOklab {
+    fn lerp(self, other: ColorOklab, t: u8) -> ColorOklab {
+        ColorOklab {
+            l: mix_i8(self.l, other.l, t),
+            a: mix_i8(self.a, other.a, t),
+            b: mix_i8(self.b, other.b, t),
+        }
+    }
+}
+
+impl Lerp for ColorOklch {
+    /// Hues take the shortest way around the hue wheel.
+    fn lerp(self, other: ColorOklch, t: u8) -> ColorOklch {
+        ColorOklch {
+            l: mix_i8(self.l, other.l, t),
+            c: mix_u8(self.c, other.c, t),
+            h: mix_hue(self.h, other.h, t),
+        }
+    }
+}
+
+impl Lerp for ColorRgb {
+    /// Blends in linear light, so that blends aren't darker than they should
+    /// be.
+    fn lerp(self, other: ColorRgb, t: u8) -> ColorRgb {
+        let from = srgb_to_linear_rgb(self);
+        let to = srgb_to_linear_rgb(other);
+        let mixed = ColorLinearRgb {
+            r: mix(from.r, to.r, t),
+            g: mix(from.g, to.g, t),
+            b: mix(from.b, to.b, t),
+        };
+        match linear_rgb_to_srgb(mixed) {
+            Ok(color) => color,
+            Err(color) => color,
+        }
+    }
+}
+
+fn mix_i8(a: i8, b: i8, t: u8) -> i8 {
+    mix(a as i32, b as i32, t) as i8
+}
+
+fn mix_u8(a: u8, b: u8, t: u8) -> u8 {
+    mix(a as i32, b as i32, t) as u8
+}
+
+// Blend from hue `a` to hue `b` (in degrees), the shortest way around.
+fn mix_hue(a: i16, b: i16, t: u8) -> i16 {
+    let mut d = (b as i32 - a as i32).rem_euclid(360);
+    if d > 180 {
+        d -= 360;
+    }
+    (a as i32 + mix(0, d, t)).rem_euclid(360) as i16
+}
+
+/// A gradient through up to `N` colors ("stops"), each at a position from 0
+/// to 65535. Sampling between two stops blends them with `Lerp`.
+#[derive(Clone, Copy, Debug)]
+pub struct Gradient<C: Lerp, const N: usize> {
+    stops: [(u16, C); N],
+    len: usize,
+}
+
+impl<C: Lerp, const N: usize> Gradient<C, N> {
+    /// A gradient from `start` at position 0 to `end` at position 65535. `N`
+    /// must be at least 2, or this won't compile.
+    pub fn new(start: C, end: C) -> Self {
+        const {
+            assert!(N >= 2, "A gradient needs room for at least two stops");
+        }
+        let mut stops = [(0, start); N];
+        stops[1] = (u16::MAX, end);
+        Gradient { stops, len: 2 }
+    }
+
+    /// The stops, in order of position.
+    pub fn stops(&self) -> &[(u16, C)] {
+        &self.stops[..self.len]
+    }
+
+    /// Add a stop. If there's no room for it, return an `Err` of its color.
+    /// Stops at the same position make a sharp edge, with the first one
+    /// added on the left.
+    pub fn add_stop(&mut self, position: u16, color: C) -> Result<(), C> {
+        if self.len == N {
+            return Err(color);
+        }
+        let i = self.stops().partition_point(|stop| stop.0 <= position);
+        self.stops.copy_within(i..self.len, i + 1);
+        self.stops[i] = (position, color);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// The color at `position`. Before the first stop or after the last one,
+    /// this is the color of that stop.
+    pub fn sample(&self, position: u16) -> C {
+        let stops = self.stops();
+        let i = stops.partition_point(|stop| stop.0 <= position);
+        if i == 0 {
+            return stops[0].1;
+        }
+        if i == stops.len() {
+            return stops[i - 1].1;
+        }
+        let (from, to) = (stops[i - 1], stops[i]);
+        let t = (position - from.0) as u32 * 255 / (to.0 - from.0) as u32;
+        from.1.lerp(to.1, t as u8)
+    }
+}
+
+// Only the stops in use matter: the rest of the array is left over from `new`.
+impl<C: Lerp + PartialEq, const N: usize> PartialEq for Gradient<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.stops() == other.stops()
+    }
+}
+
+impl<C: Lerp + Eq, const N: usize> Eq for Gradient<C, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lerp() {
+        let from = ColorLab {
+            l: 20,
+            a: -10,
+            b: 30,
+        };
+        let to = ColorLab {
+            l: 80,
+            a: 10,
+            b: -30,
+        };
+        assert_eq!(from.lerp(to, 0), from);
+        assert_eq!(from.lerp(to, 255), to);
+        assert_eq!(from.lerp(to, 128), ColorLab { l: 50, a: 0, b: 0 });
+        // `ColorLab16` keeps the fractions.
+        let (from16, to16) = (ColorLab16::from(from), ColorLab16::from(to));
+        assert_eq!(
+            from16.lerp(to16, 1),
+            ColorLab16 {
+                l: 5180,
+                a: -2540,
+                b: 7620
+            }
+        );
+
+        // The shortest way from 350 to 30 degrees is through 0.
+        let from = ColorLch {
+            l: 50,
+            c: 20,
+            h: 350,
+        };
+        let to = ColorLch {
+            l: 50,
+            c: 40,
+            h: 30,
+        };
+        assert_eq!(from.lerp(to, 64).h, 0);
+        assert_eq!(
+            from.lerp(to, 128),
+            ColorLch {
+                l: 50,
+                c: 30,
+                h: 10
+            }
+        );
+        assert_eq!(to.lerp(from, 128).h, 10);
+
+        let black = ColorRgb::black();
+        let white = ColorRgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        assert_eq!(black.lerp(white, 0), black);
+        assert_eq!(black.lerp(white, 255), white);
+        // Half as much light is brighter than half of 255 in sRGB.
+        assert_eq!(black.lerp(white, 128).r, 187);
+    }
+
+    #[test]
+    fn test_gradient() {
+        let red = ColorOklab {
+            l: 60,
+            a: 20,
+            b: 10,
+        };
+        let green = ColorOklab {
+            l: 80,
+            a: -20,
+            b: 20,
+        };
+        let blue = ColorOklab {
+            l: 40,
+            a: 0,
+            b: -30,
+        };
+        let mut gradient = Gradient::<_, 3>::new(red, blue);
+        assert_eq!(gradient.sample(0), red);
+        assert_eq!(gradient.sample(65535), blue);
+        assert_eq!(
+            gradient.sample(32768),
+            ColorOklab {
+                l: 50,
+                a: 10,
+                b: -10
+            }
+        );
+
+        assert_eq!(gradient.add_stop(16384, green), Ok(()));
+        assert_eq!(gradient.add_stop(0, green), Err(green));
+        assert_eq!(gradient.stops()[1], (16384, green));
+        assert_eq!(gradient.sample(8192), ColorOklab { l: 70, a: 0, b: 15 });
+        assert_eq!(gradient.sample(16384), green);
+
+        // Two stops at the same position make a sharp edge.
+        let mut gradient = Gradient::<_, 4>::new(red, blue);
+        gradient.add_stop(40000, green).unwrap();
+        gradient.add_stop(40000, red).unwrap();
+        assert_eq!(gradient.sample(39999), green);
+        assert_eq!(gradient.sample(40000), red);
+
+        // Only the stops in use are compared.
+        let gradient = Gradient::<_, 4>::new(red, blue);
+        let mut other = gradient;
+        other.stops[3] = (1, green);
+        assert_eq!(gradient, other);
+        other.add_stop(1, green).unwrap();
+        assert_ne!(gradient, other);
+    }
+}
@@ -36,14 +36,192 @@ pub fn inc_i32(x: &mut i32, delta: i32, min: i32, max: i32) {
     }
 }
 
+/// Blend from `a` to `b` by `t`, from 0 (all `a`) to 255 (all `b`),
+/// rounding to the nearest integer.
+pub fn mix(a: i32, b: i32, t: u8) -> i32 {
+    a + div_round((b - a) as i64 * t as i64, 255) as i32
+}
+
+/// `x / y`, rounded to the nearest integer, with halves rounded away from
+/// zero. `y` must be positive.
+pub fn div_round(x: i64, y: i64) -> i64 {
+    if x < 0 {
+        -div_round(-x, y)
+    } else {
+        (x + y / 2) / y
+    }
+}
+
 pub fn sqrt(x: i32) -> i8 {
     match SQUARES.binary_search(&x) {
         Ok(i) => i as i8,
-        Err(128) => 127 as i8,
+        Err(128) => 127,
         Err(i) => i as i8,
     }
 }
 
+/// The angle of the point (x, y) from the x axis, counterclockwise in degrees
+/// from 0 to 359. Accurate to within a degree.
+pub fn atan2(y: i32, x: i32) -> i32 {
+    let (abs_x, abs_y) = (x.unsigned_abs() as i64, y.unsigned_abs() as i64);
+    if abs_x == 0 && abs_y == 0 {
+        return 0;
+    }
+    // The ratio of the smaller to the larger, scaled by 12 bits, so that the
+    // angle is between 0 and 45 degrees.
+    let z = (abs_x.min(abs_y) << 12) / abs_x.max(abs_y);
+    // atan(z) ~= 45z + z(1 - z)(14.02 + 3.8z), scaled by 24 bits.
+    let coeff = 57426 + ((3891 * z) >> 10);
+    let scaled = 45 * z * 4096 + ((z * (4096 - z) * coeff) >> 12);
+    let mut deg = ((scaled + (1 << 23)) >> 24) as i32;
+    if abs_y > abs_x {
+        deg = 90 - deg;
+    }
+    if x < 0 {
+        deg = 180 - deg;
+    }
+    if y < 0 {
+        deg = 360 - deg;
+    }
+    deg % 360
+}
+
+/// Angles for the precise trigonometry functions are in degrees, scaled by 20
+/// bits.
+pub const DEG: i64 = 1 << 20;
+
+/// Sines, cosines and other fractions for the precise trigonometry functions
+/// are scaled by 30 bits.
+pub const ONE: i64 = 1 << 30;
+
+// `atan(2^-i)` in degrees, scaled by 20 bits, for CORDIC:
+//    https://en.wikipedia.org/wiki/CORDIC
+const ATAN: [i64; 27] = [
+    47185920, 27855475, 14718068, 7471121, 3750058, 1876857, 938658, 469357,
+    234682, 117342, 58671, 29335, 14668, 7334, 3667, 1833, 917, 458, 229, 115,
+    57, 29, 14, 7, 4, 2, 1,
+];
+
+// The product of `cos(atan(2^-i))`, scaled by 30 bits, which undoes the
+// stretching of vectors rotated by CORDIC.
+const CORDIC_GAIN: i64 = 652032874;
+
+/// Like `atan2`, but much more precise, and slower: the angle of the point
+/// (x, y) from the x axis, counterclockwise in degrees scaled by `DEG`, from 0
+/// up to 360 degrees.
+pub fn atan2_precise(y: i64, x: i64) -> i64 {
+    if x == 0 && y == 0 {
+        return 0;
+    }
+    // Rotate (|x|, |y|) down to the x axis, adding up the rotations. Scale it
+    // up first, for precision.
+    let shift = (x.abs().max(y.abs()) as u64).leading_zeros() as i64 - 24;
+    let (mut u, mut v) = (x.abs() << shift, y.abs() << shift);
+    let mut deg = 0;
+    for (i, step) in ATAN.iter().enumerate() {
+        let (du, dv) = (v >> i, u >> i);
+        if v > 0 {
+            u += du;
+            v -= dv;
+            deg += step;
+        } else {
+            u -= du;
+            v += dv;
+            deg -= step;
+        }
+    }
+    // Rounding can take the angle slightly out of the first quadrant.
+    deg = deg.clamp(0, 90 * DEG);
+    if x < 0 {
+        deg = 180 * DEG - deg;
+    }
+    if y < 0 {
+        deg = 360 * DEG - deg;
+    }
+    deg % (360 * DEG)
+}
+
+/// Like `sin`, but much more precise, and slower: the sine of `deg` (scaled
+/// by `DEG`), scaled by `ONE`. It's exactly odd, i.e. `sin(-x) = -sin(x)`.
+pub fn sin_precise(deg: i64) -> i64 {
+    let mut deg = deg.rem_euclid(360 * DEG);
+    // Rotate into -90 to 90 degrees, where CORDIC converges.
+    let mut sign = 1;
+    if deg > 270 * DEG {
+        deg -= 360 * DEG;
+    } else if deg > 90 * DEG {
+        deg -= 180 * DEG;
+        sign = -1;
+    }
+    if deg < 0 {
+        deg = -deg;
+        sign = -sign;
+    }
+    // Rotate (1, 0) by `deg`.
+    let (mut x, mut y) = (CORDIC_GAIN, 0);
+    for (i, step) in ATAN.iter().enumerate() {
+        let (dx, dy) = (y >> i, x >> i);
+        if deg >= 0 {
+            x -= dx;
+            y += dy;
+            deg -= step;
+        } else {
+            x += dx;
+            y -= dy;
+            deg += step;
+        }
+    }
+    sign * y
+}
+
+/// Like `cos`, but much more precise, and slower (see `sin_precise`).
+pub fn cos_precise(deg: i64) -> i64 {
+    sin_precise(deg + 90 * DEG)
+}
+
+/// The square root of `x`, rounded down.
+pub fn isqrt(x: u64) -> u32 {
+    let mut x = x;
+    let mut root = 0;
+    let mut bit = 1 << 62;
+    while bit > x {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if x >= root + bit {
+            x -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root as u32
+}
+
+/// The square root of `x`, rounded to the nearest integer. `x` must be less
+/// than 2^62.
+pub fn isqrt_round(x: u64) -> u32 {
+    (isqrt(4 * x) as u64).div_ceil(2) as u32
+}
+
+/// The cube root of `x`, rounded down.
+pub fn icbrt(x: u64) -> u32 {
+    let mut x = x;
+    let mut root: u64 = 0;
+    let mut shift = 63;
+    while shift >= 0 {
+        root <<= 1;
+        let bit = 3 * root * (root + 1) + 1;
+        if x >> shift >= bit {
+            x -= bit << shift;
+            root += 1;
+        }
+        shift -= 3;
+    }
+    root as u32
+}
+
 /// Table of squares, for computing the square root of an `i8`.
 pub const SQUARES: [i32; 128] = [
     0, 1, 4, 9, 16, 25, 36, 49, 64, 81, 100, 121, 144, 169, 196, 225, 256, 289,
@@ -70,3 +248,75 @@ fn test_inc() {
     inc(&mut x, -10, -10, 10);
     assert_eq!(x, -10);
 }
+
+#[test]
+fn test_div_round() {
+    assert_eq!(div_round(7, 2), 4);
+    assert_eq!(div_round(-7, 2), -4);
+    assert_eq!(div_round(8, 3), 3);
+    assert_eq!(div_round(-8, 3), -3);
+    assert_eq!(div_round(0, 5), 0);
+    assert_eq!(mix(0, 100, 128), 50);
+    assert_eq!(mix(100, 0, 128), 50);
+}
+
+#[test]
+fn test_isqrt() {
+    assert_eq!(isqrt(0), 0);
+    assert_eq!(isqrt(15), 3);
+    assert_eq!(isqrt(16), 4);
+    assert_eq!(isqrt(1 << 40), 1 << 20);
+    assert_eq!(isqrt(u64::MAX), u32::MAX);
+}
+
+#[test]
+fn test_isqrt_round() {
+    assert_eq!(isqrt_round(0), 0);
+    assert_eq!(isqrt_round(12), 3);
+    assert_eq!(isqrt_round(13), 4);
+    assert_eq!(isqrt_round(16), 4);
+    assert_eq!(isqrt_round(128 * 128 * 2), 181);
+}
+
+#[test]
+fn test_icbrt() {
+    assert_eq!(icbrt(0), 0);
+    assert_eq!(icbrt(26), 2);
+    assert_eq!(icbrt(27), 3);
+    assert_eq!(icbrt(1 << 36), 1 << 12);
+    assert_eq!(icbrt(u64::MAX), 2642245);
+}
+
+#[test]
+fn test_atan2() {
+    assert_eq!(atan2(0, 0), 0);
+    assert_eq!(atan2(0, 5), 0);
+    assert_eq!(atan2(1, 1), 45);
+    assert_eq!(atan2(1000, 1732), 30);
+    assert_eq!(atan2(1732, 1000), 60);
+    assert_eq!(atan2(3, -3), 135);
+    assert_eq!(atan2(0, -1), 180);
+    assert_eq!(atan2(-1, 0), 270);
+    assert_eq!(atan2(-1732, -1000), 240);
+    assert_eq!(atan2(-1, 1000), 0);
+}
+
+#[test]
+fn test_precise_trig() {
+    // Within about a millionth.
+    let close = |x: i64, y: i64| (x - y).abs() <= ONE >> 20;
+    assert!(close(sin_precise(30 * DEG), ONE / 2));
+    assert!(close(sin_precise(-30 * DEG), -ONE / 2));
+    assert!(close(cos_precise(60 * DEG), ONE / 2));
+    assert!(close(sin_precise(90 * DEG), ONE));
+    assert!(close(cos_precise(180 * DEG), -ONE));
+    assert_eq!(sin_precise(-47 * DEG), -sin_precise(47 * DEG));
+    // Within about a thousandth of a degree.
+    let close = |x: i64, y: i64| (x - y).abs() <= DEG >> 10;
+    assert_eq!(atan2_precise(0, 0), 0);
+    assert_eq!(atan2_precise(0, 5), 0);
+    assert!(close(atan2_precise(1, 1), 45 * DEG));
+    assert!(close(atan2_precise(1000, 1732), 30 * DEG));
+    assert!(close(atan2_precise(-1, -1), 225 * DEG));
+    assert!(close(atan2_precise(-1, 0), 270 * DEG));
+}
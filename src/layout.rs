@@ -0,0 +1,152 @@
+/// How the rows of a matrix of LEDs are wired together.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wiring {
+    /// Every row runs the same way, left to right.
+    Progressive,
+    /// Rows alternate direction, so the wire snakes back and forth. The first
+    /// row runs left to right.
+    Serpentine,
+}
+
+/// How far a matrix is turned clockwise from the way it's wired, as seen by
+/// the viewer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+/// The arrangement of a strip's lights in a 2D matrix, so that light shows can
+/// draw across a panel instead of along the wire.
+///
+/// `width` and `height` are the size of the matrix as it's wired, with rows of
+/// `width` lights starting from the top left. Coordinates are as seen by the
+/// viewer, after `rotation`, with (0, 0) at the top left.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Layout {
+    pub width: u16,
+    pub height: u16,
+    pub wiring: Wiring,
+    pub rotation: Rotation,
+}
+
+impl Layout {
+    /// A progressively wired matrix, without rotation.
+    pub fn new(width: u16, height: u16) -> Layout {
+        Layout {
+            width,
+            height,
+            wiring: Wiring::Progressive,
+            rotation: Rotation::Deg0,
+        }
+    }
+
+    /// The number of lights in the matrix.
+    pub fn len(self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// The width and height of the matrix as seen, after rotation.
+    pub fn size(self) -> (u16, u16) {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => (self.width, self.height),
+            Rotation::Deg90 | Rotation::Deg270 => (self.height, self.width),
+        }
+    }
+
+    /// The (x, y) coordinates of the light at `index` along the wire, or
+    /// `None` if it's past the end of the matrix.
+    pub fn coords(self, index: usize) -> Option<(u16, u16)> {
+        if index >= self.len() {
+            return None;
+        }
+        let (w, h) = (self.width, self.height);
+        let row = (index / w as usize) as u16;
+        let mut col = (index % w as usize) as u16;
+        if self.wiring == Wiring::Serpentine && row % 2 == 1 {
+            col = w - 1 - col;
+        }
+        Some(match self.rotation {
+            Rotation::Deg0 => (col, row),
+            Rotation::Deg90 => (h - 1 - row, col),
+            Rotation::Deg180 => (w - 1 - col, h - 1 - row),
+            Rotation::Deg270 => (row, w - 1 - col),
+        })
+    }
+
+    /// The index along the wire of the light at (x, y), or `None` if it's
+    /// outside the matrix.
+    pub fn index(self, x: u16, y: u16) -> Option<usize> {
+        let (w, h) = (self.width, self.height);
+        let (seen_w, seen_h) = self.size();
+        if x >= seen_w || y >= seen_h {
+            return None;
+        }
+        let (mut col, row) = match self.rotation {
+            Rotation::Deg0 => (x, y),
+            Rotation::Deg90 => (y, h - 1 - x),
+            Rotation::Deg180 => (w - 1 - x, h - 1 - y),
+            Rotation::Deg270 => (w - 1 - y, x),
+        };
+        if self.wiring == Wiring::Serpentine && row % 2 == 1 {
+            col = w - 1 - col;
+        }
+        Some(row as usize * w as usize + col as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let mut layout = Layout::new(3, 2);
+        assert_eq!(layout.coords(4), Some((1, 1)));
+        assert_eq!(layout.coords(6), None);
+        assert_eq!(layout.index(3, 0), None);
+
+        layout.wiring = Wiring::Serpentine;
+        assert_eq!(layout.coords(3), Some((2, 1)));
+        assert_eq!(layout.index(0, 1), Some(5));
+
+        // Turned clockwise, the first row of the wire runs down the right.
+        layout.rotation = Rotation::Deg90;
+        assert_eq!(layout.size(), (2, 3));
+        assert_eq!(layout.coords(0), Some((1, 0)));
+        assert_eq!(layout.coords(2), Some((1, 2)));
+        assert_eq!(layout.coords(3), Some((0, 2)));
+        assert_eq!(layout.index(2, 0), None);
+    }
+
+    #[test]
+    fn test_layout_round_trip() {
+        let wirings = [Wiring::Progressive, Wiring::Serpentine];
+        let rotations = [
+            Rotation::Deg0,
+            Rotation::Deg90,
+            Rotation::Deg180,
+            Rotation::Deg270,
+        ];
+        for &wiring in &wirings {
+            for &rotation in &rotations {
+                let layout = Layout {
+                    width: 4,
+                    height: 3,
+                    wiring,
+                    rotation,
+                };
+                for i in 0..layout.len() {
+                    let (x, y) = layout.coords(i).unwrap();
+                    assert_eq!(layout.index(x, y), Some(i));
+                }
+            }
+        }
+    }
+}
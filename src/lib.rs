@@ -4,16 +4,49 @@
 
 pub extern crate embedded_hal;
 
+mod blend;
+mod calibration;
 mod color;
 mod color_constants;
+mod color_hsv;
+mod color_oklab;
+mod delta_e;
 mod dotstar_strip;
+mod gamma;
+mod gradient;
 mod int_math;
+mod layout;
 mod lights;
+#[cfg(test)]
+mod mock_spi;
+#[cfg(test)]
+mod mock_strip;
+mod power_limit;
+mod remap;
 mod rng;
+mod segments;
 mod shows;
+mod spatial;
+mod ws2812_strip;
 
+pub use self::blend::{composite, BlendMode, ColorRgba};
+pub use self::calibration::Calibration;
 pub use self::color::*;
-pub use self::dotstar_strip::DotstarStrip;
+pub use self::color_hsv::{ColorHsl, ColorHsv};
+pub use self::color_oklab::{ColorOklab, ColorOklch};
+pub use self::dotstar_strip::{frame_len, Chip, DotstarStrip, MAX_BRIGHTNESS};
+pub use self::gamma::{gamma_curve, Gamma};
+pub use self::gradient::{Gradient, Lerp};
 pub use self::int_math::sqrt;
+pub use self::layout::{Layout, Rotation, Wiring};
 pub use self::lights::*;
+pub use self::power_limit::PowerLimit;
+pub use self::remap::{Mapping, Remap};
+pub use self::segments::{Segment, Segments, SegmentsError};
 pub use self::shows::*;
+pub use self::spatial::{
+    distance_gradient, planar_wave, radial_pulse, Point, POSITION_SCALE,
+};
+pub use self::ws2812_strip::{
+    Ws2812Strip, MAX_WS2812_SPI_FREQUENCY, MIN_WS2812_SPI_FREQUENCY,
+};
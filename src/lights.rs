@@ -1,4 +1,6 @@
 use crate::color::ColorRgb;
+use crate::layout::Layout;
+use crate::spatial::Point;
 
 pub enum Duration {
     Millis(u32),
@@ -9,6 +11,16 @@ pub trait LightShow {
     fn new() -> Self;
     fn next(&mut self, lights: &mut [ColorRgb]) -> Duration;
     fn update(&mut self, lights: &mut [ColorRgb]);
+
+    /// Tell the show how the lights are arranged on a matrix, so it can draw
+    /// across the matrix instead of along the strip. Shows that only draw
+    /// along the strip can ignore this.
+    fn set_layout(&mut self, _layout: Layout) {}
+
+    /// Tell the show where each light is in space, for pieces whose lights
+    /// aren't on a grid: `positions[i]` is where `lights[i]` is. Shows that
+    /// only draw along the strip can ignore this.
+    fn set_positions(&mut self, _positions: &'static [Point]) {}
 }
 
 pub trait LightStrip {
@@ -16,6 +28,31 @@ pub trait LightStrip {
     fn show(&mut self, lights: &[ColorRgb]) -> Result<(), Self::Error>;
 }
 
+/// The order in which a strip expects the color channels of each LED.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorOrder {
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+}
+
+impl ColorOrder {
+    /// Arrange the red, green, and blue channels of a color in this order.
+    pub fn arrange<T>(self, r: T, g: T, b: T) -> [T; 3] {
+        match self {
+            ColorOrder::Rgb => [r, g, b],
+            ColorOrder::Rbg => [r, b, g],
+            ColorOrder::Grb => [g, r, b],
+            ColorOrder::Gbr => [g, b, r],
+            ColorOrder::Brg => [b, r, g],
+            ColorOrder::Bgr => [b, g, r],
+        }
+    }
+}
+
 impl Duration {
     pub fn subtract(&mut self, millis: u32) {
         if let Duration::Millis(ref mut ms) = self {
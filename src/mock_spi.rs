@@ -0,0 +1,23 @@
+//! A fake SPI bus, for testing the strip drivers on the host.
+
+extern crate std;
+
+use embedded_hal::blocking::spi;
+use std::vec::Vec;
+
+/// Records every byte written to it, and how many `write` calls there were.
+#[derive(Default, Debug)]
+pub struct MockSpi {
+    pub bytes: Vec<u8>,
+    pub writes: usize,
+}
+
+impl spi::Write<u8> for MockSpi {
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), ()> {
+        self.writes += 1;
+        self.bytes.extend_from_slice(words);
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+//! A fake light strip, for testing strip adapters on the host.
+
+extern crate std;
+
+use crate::{ColorRgb, LightStrip};
+use std::vec::Vec;
+
+/// Records the last frame shown on it, and can be made to fail.
+#[derive(Default)]
+pub struct MockStrip {
+    pub lights: Vec<ColorRgb>,
+    pub fail: bool,
+}
+
+impl MockStrip {
+    pub fn failing() -> MockStrip {
+        MockStrip {
+            lights: Vec::new(),
+            fail: true,
+        }
+    }
+}
+
+impl LightStrip for MockStrip {
+    type Error = ();
+
+    fn show(&mut self, lights: &[ColorRgb]) -> Result<(), ()> {
+        if self.fail {
+            return Err(());
+        }
+        self.lights = lights.to_vec();
+        Ok(())
+    }
+}
+
+/// A grayscale light, for telling lights apart in tests.
+pub fn gray(value: u8) -> ColorRgb {
+    ColorRgb {
+        r: value,
+        g: value,
+        b: value,
+    }
+}
+
+/// The brightness of each of `lights`, made by `gray`.
+pub fn grays(lights: &[ColorRgb]) -> Vec<u8> {
+    lights.iter().map(|light| light.r).collect()
+}
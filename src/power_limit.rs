@@ -0,0 +1,121 @@
+use crate::color::ColorRgb16;
+use crate::dotstar_strip::MAX_BRIGHTNESS;
+
+use core::cmp;
+
+/// Scales brightness by this much to leave it unchanged.
+pub(crate) const FULL_SCALE: u32 = 1 << 16;
+
+/// A limit on how much current a strip of LEDs may draw. Each frame's current
+/// is estimated from its gamma corrected colors and global brightness, and if
+/// it's over the limit, the whole frame is dimmed to fit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PowerLimit {
+    /// The most current the strip may draw, in milliamps. Use `u32::MAX` to
+    /// only estimate the current, without limiting it.
+    pub max_milliamps: u32,
+    /// The current drawn by one color channel of one LED, at full PWM and
+    /// full global brightness, in microamps.
+    pub channel_microamps: u32,
+    /// The current drawn by one LED when it's dark, in microamps.
+    pub idle_microamps: u32,
+}
+
+impl PowerLimit {
+    /// A limit of `max_milliamps`, using typical APA102 currents: 20mA per
+    /// channel and 1mA per dark LED.
+    pub fn new(max_milliamps: u32) -> PowerLimit {
+        PowerLimit {
+            max_milliamps,
+            channel_microamps: 20_000,
+            idle_microamps: 1_000,
+        }
+    }
+
+    /// Estimate the current drawn by an LED, in microamps. `light` is its
+    /// gamma corrected color, and `brightness` is its global brightness.
+    pub fn microamps(self, light: ColorRgb16, brightness: u8) -> u32 {
+        let total = light.r as u64 + light.g as u64 + light.b as u64;
+        let lit = total * brightness as u64 * self.channel_microamps as u64
+            / (65535 * MAX_BRIGHTNESS as u64);
+        self.idle_microamps + lit as u32
+    }
+
+    // How much to scale each LED's brightness by (out of `FULL_SCALE`) to
+    // keep within the limit, given the estimate for a frame of `num_lights`
+    // LEDs. Only the lit part of the current can be scaled down.
+    pub(crate) fn scale(self, microamps: u32, num_lights: usize) -> u32 {
+        let max = self.max_milliamps.saturating_mul(1000);
+        let idle = self.idle_microamps.saturating_mul(num_lights as u32);
+        if microamps <= max {
+            FULL_SCALE
+        } else if max <= idle {
+            0
+        } else {
+            ((max - idle) as u64 * FULL_SCALE as u64
+                / (microamps - idle) as u64) as u32
+        }
+    }
+
+    // The estimate for a frame of `num_lights` LEDs, after it's been scaled.
+    pub(crate) fn scaled_microamps(
+        self,
+        microamps: u32,
+        num_lights: usize,
+        scale: u32,
+    ) -> u32 {
+        let idle = cmp::min(
+            self.idle_microamps.saturating_mul(num_lights as u32),
+            microamps,
+        );
+        idle + ((microamps - idle) as u64 * scale as u64 / FULL_SCALE as u64)
+            as u32
+    }
+}
+
+// Scale a (gamma corrected) color by `scale` out of `FULL_SCALE`.
+pub(crate) fn scale_color(light: ColorRgb16, scale: u32) -> ColorRgb16 {
+    if scale >= FULL_SCALE {
+        return light;
+    }
+    let channel = |c: u16| (c as u32 * scale / FULL_SCALE) as u16;
+    ColorRgb16 {
+        r: channel(light.r),
+        g: channel(light.g),
+        b: channel(light.b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_microamps() {
+        let limit = PowerLimit::new(1000);
+        let white = ColorRgb16 {
+            r: 65535,
+            g: 65535,
+            b: 65535,
+        };
+        assert_eq!(limit.microamps(ColorRgb16::black(), 31), 1_000);
+        assert_eq!(limit.microamps(white, 31), 61_000);
+        assert_eq!(limit.microamps(white, 0), 1_000);
+        let red = ColorRgb16 {
+            r: 65535,
+            g: 0,
+            b: 0,
+        };
+        assert_eq!(limit.microamps(red, 31), 21_000);
+    }
+
+    #[test]
+    fn test_scale() {
+        let limit = PowerLimit::new(20);
+        // 10 LEDs idling at 1mA each, plus 20mA lit.
+        assert_eq!(limit.scale(30_000, 10), FULL_SCALE / 2);
+        assert_eq!(limit.scaled_microamps(30_000, 10, FULL_SCALE / 2), 20_000);
+        assert_eq!(limit.scale(20_000, 10), FULL_SCALE);
+        assert_eq!(limit.scale(30_000, 20), 0);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{ColorRgb, LightStrip};
+
+use core::ops::Range;
+
+/// How the lights of a frame are rearranged to fit the way a strip is
+/// installed. Indexes are into the frame the shows render.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mapping {
+    /// The strip is installed backwards.
+    Reverse,
+    /// The strip is folded in half: the first half of the frame is shown on
+    /// both halves of the strip, mirrored about the fold.
+    Mirror,
+    /// These ranges of the frame are cut out of the strip, e.g. dead pixels.
+    /// The rest of the frame is shown in order, with the gaps closed up.
+    Skip(&'static [Range<usize>]),
+    /// Each light of the strip shows the light of the frame at its entry in
+    /// the table.
+    Table(&'static [u16]),
+}
+
+/// A strip of `N` lights that shows frames rearranged by a `Mapping`. Lights
+/// mapped past the end of the frame are black.
+pub struct Remap<S: LightStrip, const N: usize> {
+    strip: S,
+    mapping: Mapping,
+    buffer: [ColorRgb; N],
+}
+
+impl<S: LightStrip, const N: usize> Remap<S, N> {
+    pub fn new(strip: S, mapping: Mapping) -> Self {
+        Remap {
+            strip,
+            mapping,
+            buffer: [ColorRgb::black(); N],
+        }
+    }
+
+    pub fn mapping(&self) -> Mapping {
+        self.mapping
+    }
+
+    pub fn set_mapping(&mut self, mapping: Mapping) {
+        self.mapping = mapping;
+    }
+
+    pub fn strip(&self) -> &S {
+        &self.strip
+    }
+
+    pub fn strip_mut(&mut self) -> &mut S {
+        &mut self.strip
+    }
+
+    /// Give back the strip.
+    pub fn release(self) -> S {
+        self.strip
+    }
+}
+
+impl<S: LightStrip, const N: usize> LightStrip for Remap<S, N> {
+    type Error = S::Error;
+
+    fn show(&mut self, lights: &[ColorRgb]) -> Result<(), S::Error> {
+        let light =
+            |i: usize| lights.get(i).copied().unwrap_or_else(ColorRgb::black);
+        // The next index of the frame that isn't skipped.
+        let mut next = 0;
+        for (i, out) in self.buffer.iter_mut().enumerate() {
+            *out = match self.mapping {
+                Mapping::Reverse => light(N - 1 - i),
+                Mapping::Mirror => light(i.min(N - 1 - i)),
+                Mapping::Skip(ranges) => {
+                    while ranges.iter().any(|range| range.contains(&next)) {
+                        next += 1;
+                    }
+                    next += 1;
+                    light(next - 1)
+                }
+                Mapping::Table(table) => table
+                    .get(i)
+                    .map_or_else(ColorRgb::black, |&j| light(j as usize)),
+            };
+        }
+        self.strip.show(&self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mock_strip::{gray, grays, MockStrip};
+    use std::vec::Vec;
+
+    fn remap<const N: usize>(mapping: Mapping, len: u8) -> Vec<u8> {
+        let lights: Vec<ColorRgb> = (0..len).map(gray).collect();
+        let mut strip = Remap::<_, N>::new(MockStrip::default(), mapping);
+        strip.show(&lights).unwrap();
+        grays(&strip.release().lights)
+    }
+
+    #[test]
+    fn test_remap() {
+        assert_eq!(remap::<4>(Mapping::Reverse, 4), [3, 2, 1, 0]);
+        assert_eq!(remap::<4>(Mapping::Reverse, 2), [0, 0, 1, 0]);
+        assert_eq!(remap::<5>(Mapping::Mirror, 5), [0, 1, 2, 1, 0]);
+        assert_eq!(remap::<4>(Mapping::Mirror, 4), [0, 1, 1, 0]);
+
+        static DEAD: [Range<usize>; 2] = [0..1, 3..5];
+        assert_eq!(remap::<4>(Mapping::Skip(&DEAD), 8), [1, 2, 5, 6]);
+        assert_eq!(remap::<4>(Mapping::Skip(&DEAD), 6), [1, 2, 5, 0]);
+
+        static TABLE: [u16; 3] = [2, 0, 9];
+        assert_eq!(remap::<4>(Mapping::Table(&TABLE), 4), [2, 0, 0, 0]);
+    }
+}
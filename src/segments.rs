@@ -0,0 +1,167 @@
+use crate::{ColorRgb, LightStrip};
+
+/// A strip that shows one contiguous piece of a larger frame: the `N` lights
+/// starting at `offset`. Use `Segments` to send the rest of the frame to other
+/// strips.
+pub struct Segment<S: LightStrip, const N: usize> {
+    strip: S,
+    offset: usize,
+    reversed: bool,
+    buffer: [ColorRgb; N],
+}
+
+impl<S: LightStrip, const N: usize> Segment<S, N> {
+    pub fn new(strip: S, offset: usize) -> Self {
+        Segment {
+            strip,
+            offset,
+            reversed: false,
+            buffer: [ColorRgb::black(); N],
+        }
+    }
+
+    /// Whether the strip is installed backwards.
+    pub fn reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// Set whether the strip is installed backwards, so that the last light
+    /// of the segment is the first light of the strip.
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    pub fn strip(&self) -> &S {
+        &self.strip
+    }
+
+    pub fn strip_mut(&mut self) -> &mut S {
+        &mut self.strip
+    }
+
+    /// Give back the strip.
+    pub fn release(self) -> S {
+        self.strip
+    }
+}
+
+impl<S: LightStrip, const N: usize> LightStrip for Segment<S, N> {
+    type Error = S::Error;
+
+    /// Show this segment's part of `lights`. If `lights` is too short, the
+    /// rest of the segment is black.
+    fn show(&mut self, lights: &[ColorRgb]) -> Result<(), S::Error> {
+        for (i, light) in self.buffer.iter_mut().enumerate() {
+            let i = if self.reversed { N - 1 - i } else { i };
+            *light = lights
+                .get(self.offset + i)
+                .copied()
+                .unwrap_or_else(ColorRgb::black);
+        }
+        self.strip.show(&self.buffer)
+    }
+}
+
+/// Shows each frame on two strips, usually `Segment`s. Nest them to show a
+/// frame on more strips, e.g. `Segments::new(a, Segments::new(b, c))`.
+///
+/// If one strip fails, the frame is still sent to the other one, so a
+/// glitching bus doesn't black out the whole installation.
+pub struct Segments<A: LightStrip, B: LightStrip> {
+    first: A,
+    second: B,
+}
+
+/// The error from showing a frame on `Segments`: which of the strips failed,
+/// and how.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SegmentsError<A, B> {
+    First(A),
+    Second(B),
+    Both(A, B),
+}
+
+impl<A: LightStrip, B: LightStrip> Segments<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Segments { first, second }
+    }
+
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    pub fn first_mut(&mut self) -> &mut A {
+        &mut self.first
+    }
+
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    pub fn second_mut(&mut self) -> &mut B {
+        &mut self.second
+    }
+
+    /// Give back the strips.
+    pub fn release(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: LightStrip, B: LightStrip> LightStrip for Segments<A, B> {
+    type Error = SegmentsError<A::Error, B::Error>;
+
+    fn show(&mut self, lights: &[ColorRgb]) -> Result<(), Self::Error> {
+        match (self.first.show(lights), self.second.show(lights)) {
+            (Ok(()), Ok(())) => Ok(()),
+            (Err(a), Ok(())) => Err(SegmentsError::First(a)),
+            (Ok(()), Err(b)) => Err(SegmentsError::Second(b)),
+            (Err(a), Err(b)) => Err(SegmentsError::Both(a, b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_strip::{gray, grays, MockStrip};
+
+    #[test]
+    fn test_segments() {
+        let lights: [ColorRgb; 8] = core::array::from_fn(|i| gray(i as u8));
+        let mut reversed = Segment::<_, 3>::new(MockStrip::default(), 3);
+        reversed.set_reversed(true);
+        let mut segments = Segments::new(
+            Segment::<_, 3>::new(MockStrip::default(), 0),
+            Segments::new(
+                reversed,
+                Segment::<_, 4>::new(MockStrip::default(), 6),
+            ),
+        );
+        segments.show(&lights).unwrap();
+        let (first, rest) = segments.release();
+        let (second, third) = rest.release();
+        assert_eq!(grays(&first.release().lights), [0, 1, 2]);
+        assert_eq!(grays(&second.release().lights), [5, 4, 3]);
+        // Past the end of the frame is black.
+        assert_eq!(grays(&third.release().lights), [6, 7, 0, 0]);
+    }
+
+    #[test]
+    fn test_segment_errors() {
+        let lights = [gray(1), gray(2)];
+        let mut segments = Segments::new(
+            Segment::<_, 1>::new(MockStrip::failing(), 0),
+            Segment::<_, 1>::new(MockStrip::default(), 1),
+        );
+        assert_eq!(segments.show(&lights), Err(SegmentsError::First(())));
+        // The second strip was still shown.
+        assert_eq!(grays(&segments.second().strip().lights), [2]);
+
+        segments.first_mut().strip_mut().fail = false;
+        segments.second_mut().strip_mut().fail = true;
+        assert_eq!(segments.show(&lights), Err(SegmentsError::Second(())));
+        segments.first_mut().strip_mut().fail = true;
+        assert_eq!(segments.show(&lights), Err(SegmentsError::Both((), ())));
+    }
+}
@@ -1,26 +1,34 @@
 use crate::color::*;
-use crate::int_math::{cos, inc, sin};
+use crate::color_oklab::{ColorOklab, ColorOklch};
+use crate::int_math::inc;
+use crate::layout::Layout;
 use crate::lights::*;
 use crate::rng::Rng;
 use core::cmp;
 
 // Number of lights to store in state. In there are more lights in the actual
-// strip, cycle these.
+// strip, cycle these. On a matrix, they're tiled in squares of `TILE` by
+// `TILE` lights instead.
 const SIZE: usize = 64;
+const TILE: usize = 8;
 
 // How long to wait between light updates, in ms.
 const DURATION: u32 = 50;
 
 /// A demo lightshow with lights of randomly varying hue, that all average to a
-/// controllable center color.
+/// controllable center color. The hues can circle in CIE-Lab, or in Oklab,
+/// which keeps blues from drifting towards purple.
 pub struct CircleShow {
     center: ColorLab,          // the average color
-    radius: i32,               // cached max lab radius
+    radius: i32,               // cached max lab (or oklab) radius
+    oklab: bool,               // circle in oklab rather than lab?
+    oklab_center: ColorOklab,  // cached center in oklab
     rng: Rng,                  // a randomish number generator
     state: [(i32, i32); SIZE], // (hue angle in degrees/step, velocity in degrees)
     hue_change_rate: i32,      // hue angle change rate, in degrees/step/step
     speed_mode: i8,
     color_mode: i8,
+    layout: Option<Layout>,
 }
 
 impl CircleShow {
@@ -45,6 +53,11 @@ impl CircleShow {
         self.calculate_radius();
     }
 
+    pub fn toggle_oklab(&mut self) {
+        self.oklab = !self.oklab;
+        self.calculate_radius();
+    }
+
     pub fn change_red(&mut self, delta: i8) {
         inc(&mut self.center.a, delta, -60, 60);
         self.calculate_radius();
@@ -61,7 +74,12 @@ impl CircleShow {
     }
 
     fn calculate_radius(&mut self) {
-        self.radius = self.center.max_radius() as i32;
+        if self.oklab {
+            self.oklab_center = self.center.to_srgb_clamped().to_oklab();
+            self.radius = self.oklab_center.max_radius() as i32;
+        } else {
+            self.radius = self.center.max_radius() as i32;
+        }
     }
 }
 
@@ -77,11 +95,14 @@ impl LightShow for CircleShow {
         let mut show = CircleShow {
             radius: 0,
             center: ColorLab { l: 70, a: 0, b: 0 },
+            oklab: false,
+            oklab_center: ColorOklab { l: 0, a: 0, b: 0 },
             rng,
             state,
             hue_change_rate: var,
             color_mode: 0,
             speed_mode: 0,
+            layout: None,
         };
         show.calculate_radius();
         show
@@ -105,13 +126,94 @@ impl LightShow for CircleShow {
     }
 
     fn update(&mut self, lights: &mut [ColorRgb]) {
-        let center = self.center;
+        let (center, oklab) = (self.center, self.oklab_center);
         for (i, light) in lights.iter_mut().enumerate() {
-            let deg = self.state[i % SIZE].0;
-            let a = (sin(deg, self.radius) as i8).wrapping_add(center.a);
-            let b = (cos(deg, self.radius) as i8).wrapping_add(center.b);
-            let color = ColorLab { l: center.l, a, b }.to_srgb_clamped();
+            let j = match self.layout.and_then(|layout| layout.coords(i)) {
+                Some((x, y)) => x as usize % TILE + y as usize % TILE * TILE,
+                None => i % SIZE,
+            };
+            // Hues are measured from the a axis, and the state from b.
+            let h = (90 - self.state[j].0.rem_euclid(360)) as i16;
+            let c = self.radius as u8;
+            let color = if self.oklab {
+                let offset = ColorOklch { l: oklab.l, c, h }.to_oklab();
+                ColorOklab {
+                    l: oklab.l,
+                    a: offset.a.wrapping_add(oklab.a),
+                    b: offset.b.wrapping_add(oklab.b),
+                }
+                .to_srgb_clamped()
+            } else {
+                let offset = ColorLch { l: center.l, c, h }.to_lab();
+                ColorLab {
+                    l: center.l,
+                    a: offset.a.wrapping_add(center.a),
+                    b: offset.b.wrapping_add(center.b),
+                }
+                .to_srgb_clamped()
+            };
             *light = color;
         }
     }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> ColorRgb {
+        ColorRgb { r, g, b }
+    }
+
+    #[test]
+    fn test_hues() {
+        let mut show = CircleShow::new();
+        let mut lights = [ColorRgb::black(); 4];
+        show.next(&mut lights);
+        assert_eq!(
+            lights,
+            [
+                rgb(13, 190, 199),
+                rgb(35, 186, 227),
+                rgb(25, 187, 221),
+                rgb(162, 179, 108),
+            ]
+        );
+        show.toggle_color();
+        show.next(&mut lights);
+        assert_eq!(
+            lights,
+            [
+                rgb(102, 121, 141),
+                rgb(108, 116, 165),
+                rgb(103, 118, 160),
+                rgb(165, 106, 44),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_oklab() {
+        let mut show = CircleShow::new();
+        show.toggle_oklab();
+        let mut lights = [ColorRgb::black(); 4];
+        show.next(&mut lights);
+        // The same hues as in `test_hues`, around the same gray.
+        assert_eq!(
+            lights,
+            [
+                rgb(59, 191, 206),
+                rgb(85, 183, 230),
+                rgb(75, 186, 225),
+                rgb(167, 180, 94),
+            ]
+        );
+        show.toggle_oklab();
+        show.update(&mut lights);
+        assert_eq!(lights[0], rgb(13, 190, 199));
+    }
 }
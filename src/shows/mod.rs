@@ -4,7 +4,9 @@ mod strobe_show;
 mod wave_show;
 
 use crate::color::ColorRgb;
+use crate::layout::Layout;
 use crate::lights::{Duration, LightShow};
+use crate::spatial::Point;
 pub use circle_show::CircleShow;
 pub use solid_show::SolidShow;
 pub use strobe_show::StrobeShow;
@@ -49,6 +51,24 @@ impl DemoLightShows {
         false
     }
 
+    /// Tell the shows how the lights are arranged on a matrix (see
+    /// `LightShow::set_layout`).
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.solid_show.set_layout(layout);
+        self.circle_show.set_layout(layout);
+        self.wave_show.set_layout(layout);
+        self.strobe_show.set_layout(layout);
+    }
+
+    /// Tell the shows where each light is in space (see
+    /// `LightShow::set_positions`).
+    pub fn set_positions(&mut self, positions: &'static [Point]) {
+        self.solid_show.set_positions(positions);
+        self.circle_show.set_positions(positions);
+        self.wave_show.set_positions(positions);
+        self.strobe_show.set_positions(positions);
+    }
+
     pub fn button_pressed(
         &mut self,
         lights: &mut [ColorRgb],
@@ -70,7 +90,7 @@ impl DemoLightShows {
                 match which_button {
                     0 => show.toggle_speed(),
                     1 => show.toggle_color(),
-                    2 => (),
+                    2 => show.toggle_oklab(),
                     _ => panic!("Invalid button"),
                 }
             }
@@ -108,18 +128,18 @@ impl DemoLightShows {
             Mode::Solid => {
                 let show = &mut self.solid_show;
                 match which_knob {
-                    0 => show.change_brightness(10 * clicks as i8),
-                    1 => show.change_red(10 * clicks as i8),
-                    2 => show.change_yellow(10 * clicks as i8),
+                    0 => show.change_brightness(10 * clicks),
+                    1 => show.change_red(10 * clicks),
+                    2 => show.change_yellow(10 * clicks),
                     _ => panic!("Invalid knob"),
                 }
             }
             Mode::Circle => {
                 let show = &mut self.circle_show;
                 match which_knob {
-                    0 => show.change_brightness(10 * clicks as i8),
-                    1 => show.change_red(10 * clicks as i8),
-                    2 => show.change_yellow(10 * clicks as i8),
+                    0 => show.change_brightness(10 * clicks),
+                    1 => show.change_red(10 * clicks),
+                    2 => show.change_yellow(10 * clicks),
                     _ => panic!("Invalid knob"),
                 }
             }
@@ -25,6 +25,13 @@ impl SolidShow {
         self.radius = self.color.max_radius();
     }
 
+    /// Show white light with a color temperature of `kelvin` (see
+    /// `ColorRgb::from_kelvin`), e.g. 2700 for a warm evening white.
+    pub fn preset_kelvin(&mut self, kelvin: u16) {
+        self.color = ColorLab::from_kelvin(kelvin, 70);
+        self.radius = self.color.max_radius();
+    }
+
     pub fn change_brightness(&mut self, delta: i8) {
         inc(&mut self.color.l, delta, 0, 100);
         self.radius = self.color.max_radius();
@@ -1,5 +1,5 @@
 use crate::color::*;
-use crate::int_math::{cos, inc, inc_i32, sin};
+use crate::int_math::{inc, inc_i32};
 use crate::lights::*;
 
 /// A strobing light show.
@@ -59,22 +59,52 @@ impl LightShow for StrobeShow {
     }
 
     fn update(&mut self, lights: &mut [ColorRgb]) {
+        let color = ColorLch {
+            l: self.brightness,
+            c: 40,
+            // `hue` is measured from the b axis, clockwise.
+            h: (90 - self.hue) as i16,
+        };
         let color = if self.state {
-            let a = sin(self.hue, 40) as i8;
-            let b = cos(self.hue, 40) as i8;
-            ColorLab {
-                l: self.brightness,
-                a,
-                b,
-            }
-            .to_srgb_clamped()
+            color.to_srgb_clamped()
         } else {
-            let a = sin(180 + self.hue, 40) as i8;
-            let b = cos(180 + self.hue, 40) as i8;
-            ColorLab { l: 70, a, b }.to_srgb_clamped()
+            ColorLch { l: 70, ..color }
+                .rotate_hue(180)
+                .to_srgb_clamped()
         };
         for light in lights {
             *light = color;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hues() {
+        let mut show = StrobeShow::new();
+        show.change_hue(30);
+        let mut lights = [ColorRgb::black(); 1];
+        show.next(&mut lights);
+        assert_eq!(
+            lights,
+            [ColorRgb {
+                r: 224,
+                g: 156,
+                b: 110
+            }]
+        );
+        // The opposite hue.
+        show.next(&mut lights);
+        assert_eq!(
+            lights,
+            [ColorRgb {
+                r: 43,
+                g: 185,
+                b: 232
+            }]
+        );
+    }
+}
@@ -1,17 +1,22 @@
 use crate::color::*;
-use crate::int_math::{cos, inc, inc_i32, sin};
+use crate::int_math::{inc, inc_i32};
+use crate::layout::Layout;
 use crate::lights::*;
+use crate::spatial::{Point, POSITION_SCALE};
 
 /// A demo lightshow with lights whose hues cycle in a wave. The settings can
 /// control the speed of the wave (how frequently the lights move down the
 /// strip), and the curvature of the wave (how many degrees the hue varies
-/// between adjacent lights).
+/// between adjacent lights). On a matrix, or when the lights' positions are
+/// known, the wave moves across the piece from left to right.
 pub struct WaveShow {
     center: ColorLab, // average color
     delay: i32,       // delay between updates
     curvature: i32,   // number of hue degrees between adjacent lights
     state: i32,       // counts how many updates there have been
     radius: i8,       // cached max lab radius
+    layout: Option<Layout>,
+    positions: &'static [Point],
 }
 
 impl WaveShow {
@@ -58,6 +63,8 @@ impl LightShow for WaveShow {
             delay: 50,
             curvature: 1,
             state: 0,
+            layout: None,
+            positions: &[],
         };
         show.radius = show.center.max_radius();
         show
@@ -74,17 +81,60 @@ impl LightShow for WaveShow {
 
     fn update(&mut self, lights: &mut [ColorRgb]) {
         // Show the lights
-        let mut deg = (self.state * self.curvature) % 360;
-        for light in lights {
-            deg = (deg + self.curvature) % 360;
-            let a = sin(deg, self.radius as i32);
-            let b = cos(deg, self.radius as i32);
-            *light = ColorLab {
-                l: self.center.l,
-                a: self.center.a + a as i8,
-                b: self.center.b + b as i8,
-            }
-            .to_srgb_clamped();
+        let start = (self.state * self.curvature) % 360;
+        for (i, light) in lights.iter_mut().enumerate() {
+            // Lights in the same column of a matrix are the same color.
+            let pos = if let Some(point) = self.positions.get(i) {
+                point.x as i64
+            } else if let Some((x, _)) =
+                self.layout.and_then(|layout| layout.coords(i))
+            {
+                x as i64 * POSITION_SCALE as i64
+            } else {
+                i as i64 * POSITION_SCALE as i64
+            };
+            let offset = (pos + POSITION_SCALE as i64) * self.curvature as i64
+                / POSITION_SCALE as i64;
+            let deg = ((start as i64 + offset) % 360) as i32;
+            // `shift` measures hues from the a axis, and the wave from b.
+            let hue = (90 - deg) as i16;
+            *light =
+                self.center.shift(self.radius as u8, hue).to_srgb_clamped();
         }
     }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn set_positions(&mut self, positions: &'static [Point]) {
+        self.positions = positions;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> ColorRgb {
+        ColorRgb { r, g, b }
+    }
+
+    #[test]
+    fn test_hues() {
+        let mut show = WaveShow::new();
+        show.change_curvature(29);
+        let mut lights = [ColorRgb::black(); 4];
+        show.update(&mut lights);
+        // The hues turn from +b towards +a along the strip.
+        assert_eq!(
+            lights,
+            [
+                rgb(222, 157, 112),
+                rgb(237, 147, 138),
+                rgb(237, 143, 173),
+                rgb(215, 150, 207),
+            ]
+        );
+    }
 }
@@ -0,0 +1,192 @@
+use crate::int_math::{isqrt, sin};
+
+/// Positions are fixed point, with this many units per light: 256 is about the
+/// spacing between neighboring lights. Shows use this to decide how far their
+/// effects should spread.
+pub const POSITION_SCALE: i32 = 256;
+
+/// The position of a light in space, in fixed point (see `POSITION_SCALE`).
+/// For flat pieces, leave `z` at zero.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Point {
+    /// Make a point. This is a `const fn` so that position tables can be
+    /// `static`.
+    pub const fn new(x: i32, y: i32, z: i32) -> Point {
+        Point { x, y, z }
+    }
+
+    /// The distance between two points, rounded down. Distances too far for a
+    /// `u32` saturate at `u32::MAX`.
+    pub fn distance(self, other: Point) -> u32 {
+        let diff =
+            |a: i32, b: i32| (a as i64 - b as i64).unsigned_abs() as u128;
+        let dx = diff(self.x, other.x);
+        let dy = diff(self.y, other.y);
+        let dz = diff(self.z, other.z);
+        let squared = dx * dx + dy * dy + dz * dz;
+        if squared > u64::MAX as u128 {
+            // The root is at least 2^32.
+            u32::MAX
+        } else {
+            isqrt(squared as u64)
+        }
+    }
+
+    // The dot product, for projecting one point onto another.
+    fn dot(self, other: Point) -> i128 {
+        self.x as i128 * other.x as i128
+            + self.y as i128 * other.y as i128
+            + self.z as i128 * other.z as i128
+    }
+}
+
+/// A wave traveling in `direction`, with crests `wavelength` apart. Returns its
+/// height at `point`, from `-amplitude` to `amplitude`. `phase` is in degrees:
+/// increase it over time to move the wave.
+pub fn planar_wave(
+    point: Point,
+    direction: Point,
+    wavelength: u32,
+    phase: i32,
+    amplitude: i32,
+) -> i32 {
+    let len = direction.distance(Point::default());
+    if len == 0 || wavelength == 0 {
+        return scaled_sin(phase, amplitude);
+    }
+    // The projection is no longer than `point`, so it fits in an `i64`.
+    let along = (point.dot(direction) / len as i128) as i64;
+    let deg = (along % wavelength as i64 * 360 / wavelength as i64) as i32;
+    scaled_sin(deg + phase, amplitude)
+}
+
+/// A ring around `center`, `radius` from it and `width` thick on each side.
+/// Returns `amplitude` on the ring, fading to zero at its edges. Increase
+/// `radius` over time to make a pulse spread outwards.
+pub fn radial_pulse(
+    point: Point,
+    center: Point,
+    radius: u32,
+    width: u32,
+    amplitude: i32,
+) -> i32 {
+    let off = (point.distance(center) as i64 - radius as i64).unsigned_abs();
+    if off >= width as u64 {
+        return 0;
+    }
+    // The cosine of how far across the ring it is.
+    scaled_sin(90 - (off * 90 / width as u64) as i32, amplitude)
+}
+
+/// Returns `amplitude` at `center`, fading linearly to zero at `max_distance`
+/// from it.
+pub fn distance_gradient(
+    point: Point,
+    center: Point,
+    max_distance: u32,
+    amplitude: i32,
+) -> i32 {
+    let distance = point.distance(center);
+    if distance >= max_distance {
+        return 0;
+    }
+    (amplitude as i64 * (max_distance - distance) as i64 / max_distance as i64)
+        as i32
+}
+
+// `sin(deg, amplitude)`, for any amplitude: `sin` overflows for amplitudes
+// above about 66000, so scale it up in an `i64` instead.
+fn scaled_sin(deg: i32, amplitude: i32) -> i32 {
+    const UNIT: i32 = 1 << 15;
+    (sin(deg, UNIT) as i64 * amplitude as i64 / UNIT as i64) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: i32 = POSITION_SCALE;
+
+    #[test]
+    fn test_distance() {
+        let a = Point::new(ONE, 2 * ONE, 3 * ONE);
+        let b = Point::new(4 * ONE, 6 * ONE, 3 * ONE);
+        assert_eq!(a.distance(b), 5 * ONE as u32);
+        assert_eq!(b.distance(a), 5 * ONE as u32);
+        assert_eq!(a.distance(a), 0);
+        // Far apart points don't overflow.
+        let min = Point::new(i32::MIN, i32::MIN, 0);
+        let max = Point::new(i32::MAX, i32::MAX, 0);
+        assert_eq!(min.distance(max), u32::MAX);
+        let x = Point::new(i32::MAX, 0, 0);
+        assert_eq!(x.distance(Point::new(i32::MIN + 1, 0, 0)), u32::MAX - 1);
+        assert_eq!(
+            x.distance(Point::new(-ONE, 0, 0)),
+            i32::MAX as u32 + ONE as u32
+        );
+    }
+
+    #[test]
+    fn test_planar_wave() {
+        let wavelength = 4 * ONE as u32;
+        let wave = |point, direction| {
+            planar_wave(point, direction, wavelength, 0, 100)
+        };
+        let x = Point::new(ONE, 0, 0);
+        assert_eq!(wave(Point::default(), x), 0);
+        assert_eq!(wave(Point::new(ONE, 7 * ONE, 0), x), 100);
+        assert_eq!(wave(Point::new(3 * ONE, 0, -ONE), x), -100);
+        // The direction's length doesn't matter.
+        let z = Point::new(0, 0, 3 * ONE);
+        assert_eq!(wave(Point::new(ONE, 0, ONE), z), 100);
+        assert_eq!(planar_wave(Point::default(), z, wavelength, 90, 100), 100);
+        // Large amplitudes and coordinates don't overflow.
+        let amplitude = 100_000;
+        let big =
+            |point: Point| planar_wave(point, x, ONE as u32, 90, amplitude);
+        assert_eq!(big(Point::default()), amplitude);
+        assert_eq!(big(Point::new(i32::MIN, 0, 0)), amplitude);
+        assert_eq!(big(Point::new(i32::MIN + ONE / 2, 0, 0)), -amplitude);
+        let min = Point::new(i32::MIN, i32::MIN, 0);
+        assert!(planar_wave(min, min, wavelength, 0, 100).abs() <= 100);
+    }
+
+    #[test]
+    fn test_radial_pulse() {
+        let center = Point::new(ONE, ONE, 0);
+        let pulse = |x, y| {
+            radial_pulse(
+                Point::new(x, y, 0),
+                center,
+                2 * ONE as u32,
+                ONE as u32,
+                100,
+            )
+        };
+        assert_eq!(pulse(3 * ONE, ONE), 100);
+        assert_eq!(pulse(ONE, -ONE), 100);
+        assert_eq!(pulse(ONE, ONE), 0);
+        assert_eq!(pulse(4 * ONE, ONE), 0);
+        let half = pulse(ONE * 5 / 2, ONE);
+        assert!(0 < half && half < 100);
+        let big = radial_pulse(center, center, 0, ONE as u32, i32::MAX);
+        assert_eq!(big, i32::MAX);
+    }
+
+    #[test]
+    fn test_distance_gradient() {
+        let center = Point::new(0, 0, ONE);
+        let gradient =
+            |point| distance_gradient(point, center, 4 * ONE as u32, 200);
+        assert_eq!(gradient(center), 200);
+        assert_eq!(gradient(Point::new(0, ONE, ONE)), 150);
+        assert_eq!(gradient(Point::new(0, 0, -3 * ONE)), 0);
+        assert_eq!(gradient(Point::new(9 * ONE, 0, 0)), 0);
+    }
+}
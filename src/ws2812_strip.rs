@@ -0,0 +1,203 @@
+use crate::{ColorOrder, ColorRgb, Gamma, LightStrip};
+
+use core::cmp;
+use embedded_hal::blocking::spi;
+
+/// The slowest SPI frequency (in Hz) that can produce WS2812 timings.
+pub const MIN_WS2812_SPI_FREQUENCY: u32 = 2_400_000;
+
+/// The fastest SPI frequency (in Hz) that fits each WS2812 bit in a byte.
+pub const MAX_WS2812_SPI_FREQUENCY: u32 = 6_400_000;
+
+// WS2812B timings, in units of 10ns: each bit lasts 1.25us, and is high for
+// 0.4us (a zero) or 0.8us (a one).
+const BIT_PERIOD: u32 = 125;
+const ZERO_HIGH: u32 = 40;
+const ONE_HIGH: u32 = 80;
+
+// How long the line must be held low to latch the colors, in us. The original
+// WS2812B needs 50us, but newer ones need 280us.
+const RESET_MICROS: u32 = 280;
+
+/// A strip of WS2812 ("NeoPixel") LEDs, driven over SPI.
+///
+/// The WS2812 doesn't take a clock; instead each bit is a pulse whose width
+/// says whether it's a zero or a one. These pulses are made by sending
+/// several SPI bits per WS2812 bit, so only the SPI's MOSI pin is used. It
+/// should idle low.
+pub struct Ws2812Strip<SPI: spi::Write<u8>> {
+    spi_bus: SPI,
+    color_order: ColorOrder,
+    gamma: Gamma,
+    // The number of SPI bits sent for each WS2812 bit.
+    bits_per_bit: u32,
+    // The SPI bits sent for a WS2812 zero and one, in the low
+    // `bits_per_bit` bits.
+    zero: u8,
+    one: u8,
+    // The number of zero bytes that make up the reset latch.
+    reset_len: usize,
+}
+
+impl<SPI: spi::Write<u8>> Ws2812Strip<SPI> {
+    /// `spi_frequency` is the frequency the SPI bus is clocked at, in Hz. It
+    /// must be between `MIN_WS2812_SPI_FREQUENCY` and
+    /// `MAX_WS2812_SPI_FREQUENCY`: if it isn't, the SPI bus is given back as
+    /// an `Err`.
+    pub fn new(spi_bus: SPI, spi_frequency: u32) -> Result<Self, SPI> {
+        if !(MIN_WS2812_SPI_FREQUENCY..=MAX_WS2812_SPI_FREQUENCY)
+            .contains(&spi_frequency)
+        {
+            return Err(spi_bus);
+        }
+        // Number of SPI bits in `time` (in units of 10ns), rounded.
+        let spi_bits =
+            |time: u32| (spi_frequency / 1000 * time + 50_000) / 100_000;
+        let bits_per_bit = spi_bits(BIT_PERIOD);
+        let pulse = |high: u32| ((1 << high) - 1) << (bits_per_bit - high);
+        let reset_bits = spi_frequency / 1000 * RESET_MICROS / 1000;
+        Ok(Self {
+            spi_bus,
+            color_order: ColorOrder::Grb,
+            gamma: Gamma::default(),
+            bits_per_bit,
+            zero: pulse(spi_bits(ZERO_HIGH)) as u8,
+            one: pulse(spi_bits(ONE_HIGH)) as u8,
+            reset_len: (reset_bits as usize).div_ceil(8),
+        })
+    }
+
+    /// Give back the SPI bus.
+    pub fn release(self) -> SPI {
+        self.spi_bus
+    }
+
+    /// The order the strip expects each LED's color channels to be sent in.
+    pub fn color_order(&self) -> ColorOrder {
+        self.color_order
+    }
+
+    /// Set the order to send each LED's color channels in. WS2812s use
+    /// `ColorOrder::Grb` (the default).
+    pub fn set_color_order(&mut self, color_order: ColorOrder) {
+        self.color_order = color_order;
+    }
+
+    /// The gamma correction applied to colors before they're sent.
+    pub fn gamma(&self) -> Gamma {
+        self.gamma
+    }
+
+    /// Set the gamma correction applied to colors before they're sent. The
+    /// default is `Gamma::default()`, which matches `ColorRgb::correct_gamma`.
+    pub fn set_gamma(&mut self, gamma: Gamma) {
+        self.gamma = gamma;
+    }
+
+    // Encode one LED's color as SPI bits. Each LED takes 24 WS2812 bits, so
+    // the result is always a whole number of bytes: `3 * bits_per_bit`.
+    fn encode(&self, light: ColorRgb) -> ([u8; 24], usize) {
+        let light = ColorRgb::from(self.gamma.correct(light.into()));
+        let mut bytes = [0; 24];
+        let mut pos = 0;
+        for channel in self.color_order.arrange(light.r, light.g, light.b) {
+            for i in (0..8).rev() {
+                let pulse = if (channel >> i) & 1 == 1 {
+                    self.one
+                } else {
+                    self.zero
+                };
+                for j in (0..self.bits_per_bit).rev() {
+                    if (pulse >> j) & 1 == 1 {
+                        bytes[pos / 8] |= 0x80 >> (pos % 8);
+                    }
+                    pos += 1;
+                }
+            }
+        }
+        (bytes, pos / 8)
+    }
+}
+
+impl<SPI: spi::Write<u8>> LightStrip for Ws2812Strip<SPI> {
+    type Error = SPI::Error;
+
+    fn show(&mut self, lights: &[ColorRgb]) -> Result<(), SPI::Error> {
+        for light in lights {
+            let (bytes, len) = self.encode(*light);
+            self.spi_bus.write(&bytes[..len])?;
+        }
+
+        // Reset latch:
+        let zeros = [0; 32];
+        let mut remaining = self.reset_len;
+        while remaining > 0 {
+            let len = cmp::min(remaining, zeros.len());
+            self.spi_bus.write(&zeros[..len])?;
+            remaining -= len;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_spi::MockSpi;
+
+    const RED: ColorRgb = ColorRgb { r: 255, g: 0, b: 0 };
+
+    fn send(spi_frequency: u32, lights: &[ColorRgb]) -> MockSpi {
+        let mut strip =
+            Ws2812Strip::new(MockSpi::default(), spi_frequency).unwrap();
+        strip.show(lights).unwrap();
+        strip.release()
+    }
+
+    #[test]
+    fn test_3mhz() {
+        // A zero is 1000, and a one is 1100.
+        let bytes = send(3_000_000, &[RED]).bytes;
+        assert_eq!(bytes.len(), 12 + 105);
+        assert_eq!(bytes[0..4], [0x88; 4]); // green
+        assert_eq!(bytes[4..8], [0xCC; 4]); // red
+        assert_eq!(bytes[8..12], [0x88; 4]); // blue
+        assert!(bytes[12..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_2_4mhz() {
+        // A zero is 100, and a one is 110.
+        let light = ColorRgb {
+            r: 0,
+            g: 255,
+            b: 128,
+        };
+        let bytes = send(2_400_000, &[light]).bytes;
+        assert_eq!(bytes.len(), 9 + 84);
+        assert_eq!(bytes[0..3], [0b11011011, 0b01101101, 0b10110110]);
+        assert_eq!(bytes[3..6], [0b10010010, 0b01001001, 0b00100100]);
+        // 128 is gamma corrected to 37 = 0b00100101.
+        assert_eq!(bytes[6..9], [0b10010011, 0b01001001, 0b10100110]);
+    }
+
+    #[test]
+    fn test_6_4mhz() {
+        // A zero is 11100000, and a one is 11111000.
+        let mut strip =
+            Ws2812Strip::new(MockSpi::default(), 6_400_000).unwrap();
+        strip.set_color_order(ColorOrder::Rgb);
+        strip.show(&[RED, RED]).unwrap();
+        let spi = strip.release();
+        assert_eq!(spi.bytes.len(), 48 + 224);
+        assert_eq!(spi.bytes[0..8], [0b11111000; 8]);
+        assert_eq!(spi.bytes[8..24], [0b11100000; 16]);
+        assert_eq!(spi.bytes[24..48], spi.bytes[0..24]);
+    }
+
+    #[test]
+    fn test_bad_frequency() {
+        assert!(Ws2812Strip::new(MockSpi::default(), 8_000_000).is_err());
+        assert!(Ws2812Strip::new(MockSpi::default(), 2_000_000).is_err());
+    }
+}